@@ -7,12 +7,13 @@ import * as path from 'path';
 // Logging configuration
 const LOG_FILE = process.env.MCP_HARNESS_LOG || path.join(process.cwd(), 'tool-calls.log');
 
-function logToolCall(toolName: string, args: Record<string, any>, result: any) {
+function logToolCall(toolName: string, args: Record<string, any>, result: any, fault?: Record<string, any>) {
   const entry = {
-    timestamp: new Date().toISOString(),
+    timestamp: now(),
     tool: toolName,
     arguments: args,
     result: result,
+    ...(fault && { fault }),
   };
   const line = JSON.stringify(entry) + '\n';
   fs.appendFileSync(LOG_FILE, line);
@@ -118,17 +119,395 @@ const fakeEmails = [
   { id: 'MSG003', from: 'alice@company.com', to: 'emma@company.com', subject: 'Code Review Request', snippet: 'Could you take a look at PR #423...', date: '2026-02-02T14:15:00Z' },
 ];
 
+const fakeJiraIssues = [
+  { key: 'PROJ-101', summary: 'Implement user authentication', status: 'In Progress', priority: 'High', assignee: 'alice' },
+  { key: 'PROJ-102', summary: 'Fix login page CSS', status: 'Open', priority: 'Medium', assignee: 'emma' },
+  { key: 'PROJ-103', summary: 'Add API rate limiting', status: 'Done', priority: 'High', assignee: 'alice' },
+];
+
+const fakeGithubRepos = [
+  { fullName: 'facebook/react', description: 'A declarative UI library', stars: 220000, language: 'JavaScript' },
+  { fullName: 'microsoft/vscode', description: 'Visual Studio Code', stars: 155000, language: 'TypeScript' },
+  { fullName: 'torvalds/linux', description: 'Linux kernel source tree', stars: 165000, language: 'C' },
+];
+
+// Canned issues/PRs have no repo, so they show up in whichever repository is listed
+const fakeGithubIssues = [
+  { number: 1234, title: 'Bug in component rendering', state: 'open', labels: ['bug'], user: 'contributor1' },
+  { number: 1235, title: 'Feature request: dark mode', state: 'open', labels: ['enhancement'], user: 'contributor2' },
+];
+
+const fakeGithubPrs = [
+  { number: 567, title: 'Fix memory leak in worker', state: 'open', user: 'dev1', draft: false },
+  { number: 568, title: 'Add TypeScript support', state: 'open', user: 'dev2', draft: true },
+];
+
+// The harness acts as this user (sender of Slack messages, emails, etc.)
+const CURRENT_USER = { slackId: 'U001', email: 'you@company.com', jira: 'you' };
+
+// Clock and randomness
+//
+// The runner can freeze the current time (MCP_HARNESS_NOW, ISO 8601) and seed the
+// RNG (MCP_HARNESS_SEED) so IDs and timestamps are the same on every run. Without
+// them the harness uses the wall clock and Math.random.
+const FROZEN_NOW = process.env.MCP_HARNESS_NOW ? Date.parse(process.env.MCP_HARNESS_NOW) : undefined;
+const SEED = process.env.MCP_HARNESS_SEED ? parseInt(process.env.MCP_HARNESS_SEED, 10) : undefined;
+
 // Utility functions
+function currentTime(): number {
+  return FROZEN_NOW ?? Date.now();
+}
+
+function random(): number {
+  if (SEED === undefined) return Math.random();
+  // The stream position lives in the world, so a restarted server (next turn)
+  // continues the sequence instead of repeating IDs
+  const value = seededRandom(world.rngState)();
+  world.rngState = (world.rngState + 0x6D2B79F5) >>> 0;
+  return value;
+}
+
 function generateId(prefix: string): string {
-  return `${prefix}${Date.now().toString(36)}${Math.random().toString(36).substr(2, 5)}`;
+  return `${prefix}${currentTime().toString(36)}${random().toString(36).substr(2, 5)}`;
 }
 
 function now(): string {
-  return new Date().toISOString();
+  return new Date(currentTime()).toISOString();
+}
+
+// Rolled from its own stream (like fault rolls), so turning latency on doesn't
+// shift the IDs and timestamps drawn from random()
+function randomDelay(toolName: string, callNumber: number): number {
+  const roll = SEED === undefined ? Math.random() : seededRandom(SEED ^ hashString(`latency#${toolName}#${callNumber}`))();
+  return Math.floor(roll * 200) + 50;
 }
 
-function randomDelay(): number {
-  return Math.floor(Math.random() * 200) + 50;
+function sleep(ms: number): Promise<void> {
+  return new Promise(resolve => setTimeout(resolve, ms));
+}
+
+// Small seeded PRNG (mulberry32), returns floats in [0, 1)
+function seededRandom(seed: number): () => number {
+  let a = seed >>> 0;
+  return () => {
+    a = (a + 0x6D2B79F5) >>> 0;
+    let t = a;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+// FNV-1a, for turning strings into PRNG seeds
+function hashString(value: string): number {
+  let hash = 0x811C9DC5;
+  for (let i = 0; i < value.length; i++) {
+    hash ^= value.charCodeAt(i);
+    hash = Math.imul(hash, 0x01000193);
+  }
+  return hash >>> 0;
+}
+
+// World state
+//
+// Every handler reads from and writes to this store, so a created Jira issue can
+// be fetched, appended rows show up in the next sheets_read, and so on. The store
+// is seeded from the fake datasets above and saved next to the tool-call log, so
+// it survives across harness processes within one run (e.g. multi-turn sessions).
+const STATE_FILE = process.env.MCP_HARNESS_STATE || path.join(path.dirname(LOG_FILE), 'harness-state.json');
+
+type Rec = Record<string, any>;
+
+interface World {
+  users: Rec[];
+  companies: Rec[];
+  opportunities: Rec[];
+  contacts: Rec[];
+  salesforceRecords: Record<string, Rec[]>;  // object types without a fake dataset
+  files: Rec[];
+  spreadsheets: Record<string, { title: string; sheets: { name: string; data: string[][] }[] }>;
+  slackChannels: Rec[];
+  slackMessages: Rec[];
+  calendarEvents: Rec[];
+  emails: Rec[];
+  drafts: Rec[];
+  jiraIssues: Rec[];
+  githubRepos: Rec[];
+  githubIssues: Rec[];
+  githubPrs: Rec[];
+  callCounts: Record<string, number>;  // per tool, for `on_call` faults
+  rngState: number;                    // seeded RNG position, see random()
+}
+
+// Scenario-supplied fixtures (JSON file from the runner) replace whole collections,
+// e.g. { "slack": { "messages": [...] }, "jira": { "issues": [...] } }
+const FIXTURES_FILE = process.env.MCP_HARNESS_FIXTURES;
+
+const fixtureCollections: Record<string, Record<string, keyof World>> = {
+  slack: { users: 'users', channels: 'slackChannels', messages: 'slackMessages' },
+  salesforce: { accounts: 'companies', opportunities: 'opportunities', contacts: 'contacts' },
+  gdrive: { files: 'files' },
+  sheets: { spreadsheets: 'spreadsheets' },
+  calendar: { events: 'calendarEvents' },
+  gmail: { emails: 'emails', drafts: 'drafts' },
+  jira: { issues: 'jiraIssues' },
+  github: { repos: 'githubRepos', issues: 'githubIssues', prs: 'githubPrs' },
+};
+
+function loadFixtures(): Record<string, Record<string, unknown>> {
+  if (!FIXTURES_FILE || !fs.existsSync(FIXTURES_FILE)) return {};
+  return JSON.parse(fs.readFileSync(FIXTURES_FILE, 'utf-8'));
+}
+
+function seedWorld(): World {
+  const seeded: World = structuredClone({
+    users: fakeUsers,
+    companies: fakeCompanies,
+    opportunities: fakeOpportunities,
+    contacts: fakeUsers,
+    salesforceRecords: {},
+    files: fakeFiles,
+    spreadsheets: fakeSpreadsheets,
+    slackChannels: fakeSlackChannels,
+    slackMessages: fakeSlackMessages,
+    calendarEvents: fakeCalendarEvents,
+    emails: fakeEmails,
+    drafts: [],
+    jiraIssues: fakeJiraIssues,
+    githubRepos: fakeGithubRepos,
+    githubIssues: fakeGithubIssues,
+    githubPrs: fakeGithubPrs,
+    callCounts: {},
+    rngState: SEED ?? 0,
+  });
+
+  for (const [app, collections] of Object.entries(loadFixtures())) {
+    for (const [collection, data] of Object.entries(collections ?? {})) {
+      const key = fixtureCollections[app]?.[collection];
+      if (!key) {
+        console.error(`mcp-harness: ignoring unknown fixture ${app}.${collection}`);
+        continue;
+      }
+      (seeded as any)[key] = structuredClone(data);
+    }
+  }
+
+  seeded.jiraIssues = seeded.jiraIssues.map(i => ({ project: String(i.key).split('-')[0], comments: [], ...i }));
+  return seeded;
+}
+
+function loadWorld(): World {
+  try {
+    if (fs.existsSync(STATE_FILE)) {
+      return JSON.parse(fs.readFileSync(STATE_FILE, 'utf-8'));
+    }
+  } catch {
+    // Corrupt state file, start from the seed
+  }
+  return seedWorld();
+}
+
+function saveWorld() {
+  fs.writeFileSync(STATE_FILE, JSON.stringify(world, null, 2));
+}
+
+const world = loadWorld();
+
+// Salesforce field names -> fake dataset field names
+const salesforceFields: Record<string, Record<string, string>> = {
+  Account: { Id: 'id', Name: 'name', Industry: 'industry', AnnualRevenue: 'revenue' },
+  Opportunity: { Id: 'id', Name: 'name', AccountId: 'accountId', StageName: 'stage', Amount: 'amount', CloseDate: 'closeDate' },
+  Contact: { Id: 'id', Name: 'name', Email: 'email', Department: 'department' },
+};
+
+function salesforceCollection(objectType: string): Rec[] {
+  switch (objectType) {
+    case 'Account': return world.companies;
+    case 'Opportunity': return world.opportunities;
+    case 'Contact': return world.contacts;
+    default: return (world.salesforceRecords[objectType] ??= []);
+  }
+}
+
+function toSalesforce(objectType: string, record: Rec): Rec {
+  const fields = salesforceFields[objectType];
+  if (!fields) return record;
+  return Object.fromEntries(Object.entries(fields).map(([sf, local]) => [sf, record[local]]));
+}
+
+function fromSalesforce(objectType: string, data: Rec): Rec {
+  const fields = salesforceFields[objectType] ?? {};
+  return Object.fromEntries(Object.entries(data).map(([key, value]) => [fields[key] ?? key, value]));
+}
+
+// Accepts "C001", "#general" or "general"; user IDs are direct messages
+function resolveSlackChannel(channel: string): string | undefined {
+  if (!channel) return undefined;
+  if (world.slackChannels.some(c => c.id === channel) || world.users.some(u => u.id === channel)) return channel;
+  return world.slackChannels.find(c => c.name === channel.replace(/^#/, ''))?.id;
+}
+
+function nextNumber(records: Rec[], numberOf: (r: Rec) => number, start: number): number {
+  return Math.max(start - 1, ...records.map(numberOf).filter(n => !Number.isNaN(n))) + 1;
+}
+
+// A1 notation, e.g. "Deals!A2:C10", "Sheet1!A:D" or just "Deals"
+function parseRange(range: string): { sheet?: string; startRow: number; startCol: number; endRow?: number; endCol?: number } {
+  const [first, second] = (range || '').split('!');
+  const sheet = second !== undefined ? first.replace(/^'|'$/g, '') : undefined;
+  const cells = second !== undefined ? second : first;
+  const match = /^([A-Z]*)(\d*)(?::([A-Z]*)(\d*))?$/i.exec(cells || '');
+  if (!match || (second === undefined && !/\d|:/.test(cells || ''))) {
+    // No cell reference: the whole sheet (a bare name is a sheet name)
+    return { sheet: second === undefined && cells ? cells : sheet, startRow: 0, startCol: 0 };
+  }
+  const col = (letters: string) => letters
+    ? letters.toUpperCase().split('').reduce((n, ch) => n * 26 + ch.charCodeAt(0) - 64, 0) - 1
+    : undefined;
+  const row = (digits: string) => (digits ? parseInt(digits, 10) - 1 : undefined);
+  return {
+    sheet,
+    startCol: col(match[1]) ?? 0,
+    startRow: row(match[2]) ?? 0,
+    endCol: match[3] !== undefined ? col(match[3]) : col(match[1]),
+    endRow: match[3] !== undefined ? row(match[4]) : row(match[2]),
+  };
+}
+
+function columnLetter(index: number): string {
+  let letters = '';
+  for (let n = index + 1; n > 0; n = Math.floor((n - 1) / 26)) {
+    letters = String.fromCharCode(65 + ((n - 1) % 26)) + letters;
+  }
+  return letters;
+}
+
+// Very small JQL subset: `field = value`, `field != value`, `field ~ text` joined by AND
+function matchesJql(issue: Rec, jql: string): boolean {
+  const where = (jql || '').split(/\s+order\s+by\s+/i)[0];
+  const clauses = where.split(/\s+and\s+/i).map(c => c.trim()).filter(Boolean);
+  return clauses.every(clause => {
+    const m = /^(\w+)\s*(!=|=|~)\s*"?([^"]*)"?$/.exec(clause);
+    if (!m) return true;  // unsupported clause: don't filter on it
+    const [, field, op, rawValue] = m;
+    const value = rawValue.toLowerCase();
+    const key = field.toLowerCase();
+    const actual = key === 'text'
+      ? `${issue.summary} ${issue.description ?? ''}`
+      : String(issue[key] ?? '');
+    const actualLower = actual.toLowerCase();
+    if (op === '~') return actualLower.includes(value);
+    return op === '=' ? actualLower === value : actualLower !== value;
+  });
+}
+
+// Fault injection
+//
+// A scenario's fault profile (JSON file from the runner) makes specific tools fail
+// deterministically: on the Nth call, with a seeded probability, as a 429, with a
+// truncated JSON body, or by hanging past the client's timeout. Fired faults are
+// recorded in tool-calls.log under `fault`.
+const FAULTS_FILE = process.env.MCP_HARNESS_FAULTS;
+
+type FaultKind = 'error' | 'rate_limit' | 'malformed' | 'timeout';
+
+interface FaultRule {
+  tool: string;                // tool name, `*` wildcards allowed
+  kind?: FaultKind;            // default 'error'
+  on_call?: number | number[]; // 1-based call number(s) of this tool
+  probability?: number;        // 0-1, rolled with the profile's seed
+  message?: string;
+  status?: number;             // HTTP-ish status for 'error' (default 500)
+  retry_after?: number;        // seconds, for 'rate_limit' (default 30)
+  hang_ms?: number;            // for 'timeout' (default 120000)
+}
+
+interface FaultProfile {
+  seed?: number;
+  latency?: boolean;           // add 50-250ms to every call
+  rules?: FaultRule[];
+}
+
+function loadFaults(): FaultProfile {
+  if (!FAULTS_FILE || !fs.existsSync(FAULTS_FILE)) return {};
+  return JSON.parse(fs.readFileSync(FAULTS_FILE, 'utf-8'));
+}
+
+const faults = loadFaults();
+
+function globMatch(pattern: string, name: string): boolean {
+  const regex = pattern.split('*').map(part => part.replace(/[.+?^${}()|[\]\\]/g, '\\$&')).join('.*');
+  return new RegExp(`^${regex}$`).test(name);
+}
+
+// First matching rule wins. Probabilities are rolled per (tool, call, rule), so the
+// outcome doesn't depend on how calls to other tools were interleaved.
+function pickFault(toolName: string, callNumber: number): (FaultRule & { index: number }) | undefined {
+  for (const [index, rule] of (faults.rules ?? []).entries()) {
+    if (!globMatch(rule.tool, toolName)) continue;
+    if (rule.on_call !== undefined && ![rule.on_call].flat().includes(callNumber)) continue;
+    if (rule.probability !== undefined) {
+      const roll = seededRandom((faults.seed ?? SEED ?? 1) ^ hashString(`${toolName}#${callNumber}#${index}`))();
+      if (roll >= rule.probability) continue;
+    }
+    return { ...rule, index };
+  }
+  return undefined;
+}
+
+async function injectFault(
+  toolName: string,
+  args: Record<string, any>,
+  fault: FaultRule & { index: number },
+  callNumber: number
+): Promise<{ content: { type: 'text'; text: string }[]; isError?: boolean }> {
+  const kind = fault.kind ?? 'error';
+  const fired = { kind, rule: fault.index, call: callNumber };
+  const respond = (result: any, isError = true) => ({
+    content: [{ type: 'text' as const, text: typeof result === 'string' ? result : JSON.stringify(result, null, 2) }],
+    isError,
+  });
+
+  switch (kind) {
+    case 'rate_limit': {
+      const retryAfter = fault.retry_after ?? 30;
+      const result = {
+        success: false,
+        status: 429,
+        error: 'rate_limited',
+        message: fault.message ?? `Rate limit exceeded. Retry after ${retryAfter} seconds.`,
+        retryAfter,
+      };
+      logToolCall(toolName, args, result, { ...fired, retryAfter });
+      return respond(result);
+    }
+
+    case 'malformed': {
+      // The call goes through, only the response body is cut short
+      const result = await handleTool(toolName, args);
+      saveWorld();
+      const text = JSON.stringify(result, null, 2);
+      const truncated = text.slice(0, Math.max(1, Math.floor(text.length / 2)));
+      logToolCall(toolName, args, result, { ...fired, response: truncated });
+      return respond(truncated, false);
+    }
+
+    case 'timeout': {
+      const hangMs = fault.hang_ms ?? 120000;
+      const result = { success: false, status: 504, error: 'gateway_timeout', message: fault.message ?? 'Upstream request timed out' };
+      // Log before hanging: the client may give up and kill us first
+      logToolCall(toolName, args, result, { ...fired, hangMs });
+      await sleep(hangMs);
+      return respond(result);
+    }
+
+    default: {
+      const status = fault.status ?? 500;
+      const result = { success: false, status, error: fault.message ?? 'Internal server error' };
+      logToolCall(toolName, args, result, { ...fired, status });
+      return respond(result);
+    }
+  }
 }
 
 // Tool definitions
@@ -638,20 +1017,21 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     case 'gdrive_search': {
       const query = (args.query || '').toLowerCase();
       const limit = args.limit || 10;
-      const results = fakeFiles.filter(f =>
+      const results = world.files.filter(f =>
         f.name.toLowerCase().includes(query) ||
         f.owner.toLowerCase().includes(query)
-      ).slice(0, limit);
+      ).slice(0, limit).map(({ content, ...meta }) => meta);
       return { success: true, files: results, totalResults: results.length, query: args.query };
     }
 
     case 'gdrive_read_file': {
-      const file = fakeFiles.find(f => f.id === args.fileId);
+      const file = world.files.find(f => f.id === args.fileId);
       if (!file) return { success: false, error: `File not found: ${args.fileId}` };
+      const { content, ...meta } = file;
       return {
         success: true,
-        file: file,
-        content: `[Simulated content for ${file.name}]\n\nThis is placeholder content representing the file "${file.name}".\nIn a real implementation, this would contain the actual file contents.`,
+        file: meta,
+        content: content ?? `[Simulated content for ${file.name}]\n\nThis is placeholder content representing the file "${file.name}".\nIn a real implementation, this would contain the actual file contents.`,
       };
     }
 
@@ -662,68 +1042,108 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
         mimeType: args.mimeType || 'text/plain',
         size: (args.content || '').length,
         modifiedTime: timestamp,
-        owner: 'you@company.com',
+        owner: CURRENT_USER.email,
+        ...(args.folderId && { folderId: args.folderId }),
       };
+      world.files.push({ ...newFile, content: args.content || '' });
       return { success: true, file: newFile, message: 'File created successfully' };
     }
 
     case 'gdrive_share_file': {
+      const file = world.files.find(f => f.id === args.fileId);
+      if (!file) return { success: false, error: `File not found: ${args.fileId}` };
+      const permission = { id: generateId('PERM'), email: args.email, role: args.role || 'reader' };
+      file.permissions = [...(file.permissions ?? []), permission];
       return {
         success: true,
         fileId: args.fileId,
         sharedWith: args.email,
-        role: args.role || 'reader',
-        permissionId: generateId('PERM'),
-        message: `File shared with ${args.email} as ${args.role || 'reader'}`,
+        role: permission.role,
+        permissionId: permission.id,
+        message: `File shared with ${args.email} as ${permission.role}`,
       };
     }
 
     // Google Sheets
     case 'sheets_read': {
-      const sheet = fakeSpreadsheets[args.spreadsheetId];
-      if (!sheet) return { success: false, error: `Spreadsheet not found: ${args.spreadsheetId}` };
-      const sheetData = sheet.sheets[0];
+      const spreadsheet = world.spreadsheets[args.spreadsheetId];
+      if (!spreadsheet) return { success: false, error: `Spreadsheet not found: ${args.spreadsheetId}` };
+      const range = parseRange(args.range);
+      const sheetData = spreadsheet.sheets.find(s => s.name === range.sheet) ?? spreadsheet.sheets[0];
+      const values = sheetData.data
+        .slice(range.startRow, range.endRow === undefined ? undefined : range.endRow + 1)
+        .map(row => row.slice(range.startCol, range.endCol === undefined ? undefined : range.endCol + 1));
       return {
         success: true,
         spreadsheetId: args.spreadsheetId,
         range: args.range,
-        values: sheetData.data,
+        values,
         majorDimension: 'ROWS',
       };
     }
 
     case 'sheets_write': {
+      const spreadsheet = world.spreadsheets[args.spreadsheetId];
+      if (!spreadsheet) return { success: false, error: `Spreadsheet not found: ${args.spreadsheetId}` };
+      const range = parseRange(args.range);
+      let sheetData = range.sheet ? spreadsheet.sheets.find(s => s.name === range.sheet) : spreadsheet.sheets[0];
+      if (!sheetData) {
+        sheetData = { name: range.sheet!, data: [] };
+        spreadsheet.sheets.push(sheetData);
+      }
+      const values: string[][] = args.values || [];
+      values.forEach((row, r) => {
+        const target = (sheetData!.data[range.startRow + r] ??= []);
+        row.forEach((value, c) => {
+          while (target.length < range.startCol + c) target.push('');
+          target[range.startCol + c] = String(value);
+        });
+      });
+      for (let r = 0; r < sheetData.data.length; r++) sheetData.data[r] ??= [];
       return {
         success: true,
         spreadsheetId: args.spreadsheetId,
         updatedRange: args.range,
-        updatedRows: (args.values || []).length,
-        updatedColumns: (args.values?.[0] || []).length,
-        updatedCells: (args.values || []).flat().length,
+        updatedRows: values.length,
+        updatedColumns: (values[0] || []).length,
+        updatedCells: values.flat().length,
       };
     }
 
     case 'sheets_append': {
+      const spreadsheet = world.spreadsheets[args.spreadsheetId];
+      if (!spreadsheet) return { success: false, error: `Spreadsheet not found: ${args.spreadsheetId}` };
+      const range = parseRange(args.range);
+      const sheetData = spreadsheet.sheets.find(s => s.name === range.sheet) ?? spreadsheet.sheets[0];
+      const values: string[][] = (args.values || []).map((row: any[]) => row.map(String));
+      const firstRow = sheetData.data.length + 1;
+      sheetData.data.push(...values);
+      const width = Math.max(1, ...values.map(row => row.length));
       return {
         success: true,
         spreadsheetId: args.spreadsheetId,
         tableRange: args.range,
         updates: {
-          updatedRange: `${args.range.split('!')[0]}!A${Math.floor(Math.random() * 100) + 10}`,
-          updatedRows: (args.values || []).length,
-          updatedCells: (args.values || []).flat().length,
+          updatedRange: `${sheetData.name}!A${firstRow}:${columnLetter(width - 1)}${firstRow + Math.max(values.length, 1) - 1}`,
+          updatedRows: values.length,
+          updatedCells: values.flat().length,
         },
       };
     }
 
     case 'sheets_create': {
       const newId = generateId('SHEET');
+      const sheetNames: string[] = args.sheetNames?.length ? args.sheetNames : ['Sheet1'];
+      world.spreadsheets[newId] = {
+        title: args.title,
+        sheets: sheetNames.map(name => ({ name, data: structuredClone(args.initialData?.[name] ?? []) })),
+      };
       return {
         success: true,
         spreadsheetId: newId,
         spreadsheetUrl: `https://docs.google.com/spreadsheets/d/${newId}`,
         title: args.title,
-        sheets: (args.sheetNames || ['Sheet1']).map((name: string, i: number) => ({
+        sheets: sheetNames.map((name: string, i: number) => ({
           sheetId: i,
           title: name,
         })),
@@ -736,11 +1156,14 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
       let records: any[] = [];
 
       if (soql.includes('account')) {
-        records = fakeCompanies.map(c => ({ Id: c.id, Name: c.name, Industry: c.industry, AnnualRevenue: c.revenue }));
+        records = world.companies.map(c => toSalesforce('Account', c));
       } else if (soql.includes('opportunity')) {
-        records = fakeOpportunities.map(o => ({ Id: o.id, Name: o.name, StageName: o.stage, Amount: o.amount, CloseDate: o.closeDate }));
+        records = world.opportunities.map(o => toSalesforce('Opportunity', o));
       } else if (soql.includes('contact') || soql.includes('user')) {
-        records = fakeUsers.map(u => ({ Id: u.id, Name: u.name, Email: u.email, Department: u.department }));
+        records = world.contacts.map(u => toSalesforce('Contact', u));
+      } else {
+        const objectType = Object.keys(world.salesforceRecords).find(t => soql.includes(t.toLowerCase()));
+        records = objectType ? world.salesforceRecords[objectType] : [];
       }
 
       return {
@@ -752,18 +1175,16 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     }
 
     case 'salesforce_get_record': {
-      let record: any = null;
-      if (args.objectType === 'Account') {
-        record = fakeCompanies.find(c => c.id === args.recordId);
-      } else if (args.objectType === 'Opportunity') {
-        record = fakeOpportunities.find(o => o.id === args.recordId);
-      }
+      const record = salesforceCollection(args.objectType).find(r => (r.id ?? r.Id) === args.recordId);
       if (!record) return { success: false, error: `Record not found: ${args.recordId}` };
       return { success: true, record };
     }
 
     case 'salesforce_create_record': {
       const newId = generateId(args.objectType?.substring(0, 3).toUpperCase() || 'REC');
+      const collection = salesforceCollection(args.objectType);
+      const fields = fromSalesforce(args.objectType, args.data || {});
+      collection.push(salesforceFields[args.objectType] ? { ...fields, id: newId } : { ...fields, Id: newId });
       return {
         success: true,
         id: newId,
@@ -773,6 +1194,9 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     }
 
     case 'salesforce_update_record': {
+      const record = salesforceCollection(args.objectType).find(r => (r.id ?? r.Id) === args.recordId);
+      if (!record) return { success: false, error: `Record not found: ${args.recordId}` };
+      Object.assign(record, fromSalesforce(args.objectType, args.data || {}));
       return {
         success: true,
         id: args.recordId,
@@ -785,31 +1209,48 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     case 'salesforce_search': {
       const term = (args.searchTerm || '').toLowerCase();
       const results: any[] = [];
-      fakeCompanies.filter(c => c.name.toLowerCase().includes(term)).forEach(c => results.push({ type: 'Account', ...c }));
-      fakeUsers.filter(u => u.name.toLowerCase().includes(term)).forEach(u => results.push({ type: 'Contact', ...u }));
+      world.companies.filter(c => c.name.toLowerCase().includes(term)).forEach(c => results.push({ type: 'Account', ...c }));
+      world.contacts.filter(u => u.name.toLowerCase().includes(term)).forEach(u => results.push({ type: 'Contact', ...u }));
       return { success: true, searchRecords: results.slice(0, args.limit || 20) };
     }
 
     // Slack
     case 'slack_send_message': {
+      const channelId = resolveSlackChannel(args.channel);
+      if (!channelId) return { success: false, ok: false, error: 'channel_not_found' };
+      const ts = `${(currentTime() / 1000).toFixed(0)}.${String(world.slackMessages.length + 1).padStart(6, '0')}`;
+      const message = {
+        channel: channelId,
+        user: CURRENT_USER.slackId,
+        text: args.text,
+        ts,
+        ...(args.threadTs && { threadTs: args.threadTs }),
+      };
+      world.slackMessages.push(message);
       return {
         success: true,
         ok: true,
-        channel: args.channel,
-        ts: `${Date.now() / 1000}.000100`,
-        message: { text: args.text, user: 'U001', ts: `${Date.now() / 1000}.000100` },
+        channel: channelId,
+        ts,
+        message: { text: args.text, user: CURRENT_USER.slackId, ts },
       };
     }
 
     case 'slack_get_messages': {
-      const channelId = args.channel.startsWith('#') ? fakeSlackChannels.find(c => c.name === args.channel.slice(1))?.id : args.channel;
-      const messages = fakeSlackMessages.filter(m => m.channel === channelId).slice(0, args.limit || 20);
-      return { success: true, ok: true, messages, hasMore: false };
+      const channelId = resolveSlackChannel(args.channel);
+      const messages = world.slackMessages.filter(m =>
+        m.channel === channelId &&
+        (!args.oldest || parseFloat(m.ts) > parseFloat(args.oldest)) &&
+        (!args.latest || parseFloat(m.ts) < parseFloat(args.latest))
+      );
+      const limit = args.limit || 20;
+      return { success: true, ok: true, messages: messages.slice(0, limit), hasMore: messages.length > limit };
     }
 
     case 'slack_search_messages': {
       const query = (args.query || '').toLowerCase();
-      const matches = fakeSlackMessages.filter(m => m.text.toLowerCase().includes(query));
+      const matches = world.slackMessages.filter(m => m.text.toLowerCase().includes(query));
+      if (args.sort === 'timestamp') matches.sort((a, b) => parseFloat(b.ts) - parseFloat(a.ts));
       return {
         success: true,
         ok: true,
@@ -819,30 +1260,35 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     }
 
     case 'slack_list_channels': {
-      return { success: true, ok: true, channels: fakeSlackChannels };
+      return { success: true, ok: true, channels: world.slackChannels.slice(0, args.limit || 50) };
     }
 
     case 'slack_get_user_info': {
-      const user = fakeUsers.find(u => u.id === args.userId);
+      const user = world.users.find(u => u.id === args.userId);
       if (!user) return { success: false, ok: false, error: 'user_not_found' };
       return { success: true, ok: true, user: { ...user, realName: user.name, displayName: user.name.split(' ')[0] } };
     }
 
     case 'slack_set_status': {
-      return {
-        success: true,
-        ok: true,
-        profile: {
-          statusText: args.statusText,
-          statusEmoji: args.statusEmoji || ':speech_balloon:',
-          statusExpiration: args.expirationMinutes ? Date.now() + args.expirationMinutes * 60000 : 0,
-        },
+      const profile = {
+        statusText: args.statusText,
+        statusEmoji: args.statusEmoji || ':speech_balloon:',
+        statusExpiration: args.expirationMinutes ? currentTime() + args.expirationMinutes * 60000 : 0,
       };
+      const me = world.users.find(u => u.id === CURRENT_USER.slackId);
+      if (me) Object.assign(me, profile);
+      return { success: true, ok: true, profile };
     }
 
     // Calendar
     case 'calendar_list_events': {
-      return { success: true, items: fakeCalendarEvents };
+      const timeMin = args.timeMin ? Date.parse(args.timeMin) : NaN;
+      const timeMax = args.timeMax ? Date.parse(args.timeMax) : NaN;
+      const items = world.calendarEvents
+        .filter(e => Number.isNaN(timeMin) || Date.parse(e.end) >= timeMin)
+        .filter(e => Number.isNaN(timeMax) || Date.parse(e.start) <= timeMax)
+        .slice(0, args.maxResults || 10);
+      return { success: true, items };
     }
 
     case 'calendar_create_event': {
@@ -853,98 +1299,153 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
         start: args.start,
         end: args.end,
         attendees: args.attendees || [],
+        ...(args.location && { location: args.location }),
         htmlLink: `https://calendar.google.com/event?eid=${generateId('E')}`,
       };
+      world.calendarEvents.push(newEvent);
       return { success: true, event: newEvent };
     }
 
     case 'calendar_update_event': {
-      return {
-        success: true,
-        event: {
-          id: args.eventId,
-          ...(args.summary && { summary: args.summary }),
-          ...(args.description && { description: args.description }),
-          ...(args.start && { start: args.start }),
-          ...(args.end && { end: args.end }),
-          updated: timestamp,
-        },
-      };
+      const event = world.calendarEvents.find(e => e.id === args.eventId);
+      if (!event) return { success: false, error: `Event not found: ${args.eventId}` };
+      Object.assign(event, {
+        ...(args.summary && { summary: args.summary }),
+        ...(args.description && { description: args.description }),
+        ...(args.start && { start: args.start }),
+        ...(args.end && { end: args.end }),
+        updated: timestamp,
+      });
+      return { success: true, event };
     }
 
     case 'calendar_delete_event': {
+      const index = world.calendarEvents.findIndex(e => e.id === args.eventId);
+      if (index === -1) return { success: false, error: `Event not found: ${args.eventId}` };
+      world.calendarEvents.splice(index, 1);
       return { success: true, deleted: true, eventId: args.eventId };
     }
 
     // Gmail
     case 'gmail_search': {
       const query = (args.query || '').toLowerCase();
-      const results = fakeEmails.filter(e =>
+      const results = world.emails.filter(e =>
         e.subject.toLowerCase().includes(query) ||
         e.from.toLowerCase().includes(query) ||
         e.snippet.toLowerCase().includes(query)
-      );
+      ).map(({ body, ...summary }) => summary);
       return { success: true, messages: results.slice(0, args.maxResults || 10), resultSizeEstimate: results.length };
     }
 
     case 'gmail_read_message': {
-      const email = fakeEmails.find(e => e.id === args.messageId);
+      const email = world.emails.find(e => e.id === args.messageId);
       if (!email) return { success: false, error: `Message not found: ${args.messageId}` };
       return {
         success: true,
         message: {
           ...email,
-          body: `Full body of email: "${email.subject}"\n\n${email.snippet}\n\n[Additional content would appear here in a real implementation]`,
+          body: email.body ?? `Full body of email: "${email.subject}"\n\n${email.snippet}\n\n[Additional content would appear here in a real implementation]`,
         },
       };
     }
 
     case 'gmail_send': {
+      const id = generateId('MSG');
+      const threadId = args.replyToMessageId
+        ? world.emails.find(e => e.id === args.replyToMessageId)?.threadId ?? generateId('THR')
+        : generateId('THR');
+      world.emails.push({
+        id,
+        threadId,
+        from: CURRENT_USER.email,
+        to: (args.to || []).join(', '),
+        ...(args.cc && { cc: args.cc }),
+        ...(args.bcc && { bcc: args.bcc }),
+        subject: args.subject,
+        snippet: String(args.body || '').slice(0, 80),
+        body: args.body,
+        date: timestamp,
+        labelIds: ['SENT'],
+      });
       return {
         success: true,
-        id: generateId('MSG'),
-        threadId: generateId('THR'),
+        id,
+        threadId,
         labelIds: ['SENT'],
         message: `Email sent to ${(args.to || []).join(', ')}`,
       };
     }
 
     case 'gmail_create_draft': {
-      return {
-        success: true,
+      const draft = {
         id: generateId('DRF'),
         message: { id: generateId('MSG'), threadId: generateId('THR') },
+        to: args.to || [],
+        subject: args.subject,
+        body: args.body,
+        created: timestamp,
+      };
+      world.drafts.push(draft);
+      return {
+        success: true,
+        id: draft.id,
+        message: draft.message,
       };
     }
 
     // Jira
     case 'jira_search_issues': {
-      const issues = [
-        { key: 'PROJ-101', summary: 'Implement user authentication', status: 'In Progress', priority: 'High', assignee: 'alice' },
-        { key: 'PROJ-102', summary: 'Fix login page CSS', status: 'Open', priority: 'Medium', assignee: 'emma' },
-        { key: 'PROJ-103', summary: 'Add API rate limiting', status: 'Done', priority: 'High', assignee: 'alice' },
-      ];
-      return { success: true, issues, total: issues.length, maxResults: args.maxResults || 50 };
+      const issues = world.jiraIssues
+        .filter(i => matchesJql(i, args.jql))
+        .map(({ key, summary, status, priority, assignee }) => ({ key, summary, status, priority, assignee }));
+      const maxResults = args.maxResults || 50;
+      return { success: true, issues: issues.slice(0, maxResults), total: issues.length, maxResults };
     }
 
     case 'jira_get_issue': {
+      const issue = world.jiraIssues.find(i => i.key === args.issueKey);
+      if (!issue) return { success: false, error: `Issue does not exist: ${args.issueKey}` };
+      const assignee = world.users.find(u => u.name.split(' ')[0].toLowerCase() === String(issue.assignee).toLowerCase());
       return {
         success: true,
-        key: args.issueKey,
+        key: issue.key,
         fields: {
-          summary: `Issue ${args.issueKey}`,
-          status: { name: 'In Progress' },
-          priority: { name: 'High' },
-          assignee: { displayName: 'Alice Johnson' },
-          description: 'Detailed description of the issue...',
-          created: '2026-01-15T10:00:00Z',
-          updated: timestamp,
+          summary: issue.summary,
+          status: { name: issue.status },
+          priority: { name: issue.priority },
+          assignee: issue.assignee ? { displayName: assignee?.name ?? issue.assignee } : null,
+          description: issue.description ?? 'Detailed description of the issue...',
+          labels: issue.labels ?? [],
+          created: issue.created ?? '2026-01-15T10:00:00Z',
+          updated: issue.updated ?? issue.created ?? '2026-01-15T10:00:00Z',
+          ...(args.expand?.includes('comments') && { comments: issue.comments ?? [] }),
         },
       };
     }
 
     case 'jira_create_issue': {
-      const issueKey = `${args.projectKey}-${Math.floor(Math.random() * 900) + 100}`;
+      const project = args.projectKey;
+      const number = nextNumber(
+        world.jiraIssues.filter(i => i.project === project),
+        i => parseInt(String(i.key).split('-')[1], 10),
+        100
+      );
+      const issueKey = `${project}-${number}`;
+      world.jiraIssues.push({
+        key: issueKey,
+        project,
+        issueType: args.issueType,
+        summary: args.summary,
+        description: args.description,
+        status: 'Open',
+        priority: args.priority || 'Medium',
+        assignee: args.assignee,
+        labels: args.labels || [],
+        reporter: CURRENT_USER.jira,
+        created: timestamp,
+        updated: timestamp,
+        comments: [],
+      });
       return {
         success: true,
         id: generateId(''),
@@ -954,52 +1455,80 @@ async function handleTool(name: string, args: Record<string, any>): Promise<any>
     }
 
     case 'jira_update_issue': {
+      const issue = world.jiraIssues.find(i => i.key === args.issueKey);
+      if (!issue) return { success: false, error: `Issue does not exist: ${args.issueKey}` };
+      Object.assign(issue, args.fields || {}, args.transition ? { status: args.transition } : {}, { updated: timestamp });
       return { success: true, key: args.issueKey, updated: true };
     }
 
     case 'jira_add_comment': {
-      return {
-        success: true,
+      const issue = world.jiraIssues.find(i => i.key === args.issueKey);
+      if (!issue) return { success: false, error: `Issue does not exist: ${args.issueKey}` };
+      const comment = {
         id: generateId('CMT'),
         issueKey: args.issueKey,
         body: args.body,
+        author: CURRENT_USER.jira,
         created: timestamp,
       };
+      issue.comments = [...(issue.comments ?? []), comment];
+      return { success: true, ...comment };
     }
 
     // GitHub
     case 'github_search_repos': {
-      const repos = [
-        { fullName: 'facebook/react', description: 'A declarative UI library', stars: 220000, language: 'JavaScript' },
-        { fullName: 'microsoft/vscode', description: 'Visual Studio Code', stars: 155000, language: 'TypeScript' },
-        { fullName: 'torvalds/linux', description: 'Linux kernel source tree', stars: 165000, language: 'C' },
-      ];
+      // Qualifiers like "stars:>100" are ignored; any remaining term has to match
+      const terms = (args.query || '').toLowerCase().split(/\s+/).filter((t: string) => t && !t.includes(':'));
+      const repos = world.githubRepos.filter(r => {
+        const haystack = `${r.fullName} ${r.description} ${r.language}`.toLowerCase();
+        return terms.length === 0 || terms.some((t: string) => haystack.includes(t));
+      });
+      const sortKey = args.sort === 'updated' || args.sort === 'forks' ? undefined : 'stars';
+      if (sortKey) repos.sort((a, b) => b[sortKey] - a[sortKey]);
       return { success: true, totalCount: repos.length, items: repos.slice(0, args.limit || 10) };
     }
 
     case 'github_list_issues': {
-      const issues = [
-        { number: 1234, title: 'Bug in component rendering', state: 'open', labels: ['bug'], user: 'contributor1' },
-        { number: 1235, title: 'Feature request: dark mode', state: 'open', labels: ['enhancement'], user: 'contributor2' },
-      ];
-      return { success: true, issues };
+      const repo = `${args.owner}/${args.repo}`;
+      const state = args.state || 'open';
+      const issues = world.githubIssues
+        .filter(i => !i.repo || i.repo === repo)
+        .filter(i => state === 'all' || i.state === state)
+        .filter(i => !args.labels?.length || args.labels.every((l: string) => i.labels?.includes(l)))
+        .map(({ repo: _repo, ...issue }) => issue);
+      return { success: true, issues: issues.slice(0, args.limit || 30) };
     }
 
     case 'github_create_issue': {
+      const repo = `${args.owner}/${args.repo}`;
+      const number = nextNumber(world.githubIssues.filter(i => !i.repo || i.repo === repo), i => i.number, 1);
+      const issue = {
+        repo,
+        number,
+        title: args.title,
+        body: args.body,
+        state: 'open',
+        labels: args.labels || [],
+        assignees: args.assignees || [],
+        user: 'you',
+      };
+      world.githubIssues.push(issue);
       return {
         success: true,
-        number: Math.floor(Math.random() * 9000) + 1000,
+        number,
         title: args.title,
-        htmlUrl: `https://github.com/${args.owner}/${args.repo}/issues/${Math.floor(Math.random() * 9000) + 1000}`,
+        htmlUrl: `https://github.com/${repo}/issues/${number}`,
       };
     }
 
     case 'github_list_prs': {
-      const prs = [
-        { number: 567, title: 'Fix memory leak in worker', state: 'open', user: 'dev1', draft: false },
-        { number: 568, title: 'Add TypeScript support', state: 'open', user: 'dev2', draft: true },
-      ];
-      return { success: true, pullRequests: prs };
+      const repo = `${args.owner}/${args.repo}`;
+      const state = args.state || 'open';
+      const prs = world.githubPrs
+        .filter(p => !p.repo || p.repo === repo)
+        .filter(p => state === 'all' || p.state === state)
+        .map(({ repo: _repo, ...pr }) => pr);
+      return { success: true, pullRequests: prs.slice(0, args.limit || 30) };
     }
 
     default:
@@ -1021,7 +1550,21 @@ server.setRequestHandler(CallToolRequestSchema, async (request) => {
   const toolName = request.params.name;
   const args = (request.params.arguments || {}) as Record<string, any>;
 
+  const callNumber = (world.callCounts[toolName] ?? 0) + 1;
+  world.callCounts[toolName] = callNumber;
+
+  if (faults.latency) {
+    await sleep(randomDelay(toolName, callNumber));
+  }
+
+  const fault = pickFault(toolName, callNumber);
+  if (fault) {
+    saveWorld();
+    return injectFault(toolName, args, fault, callNumber);
+  }
+
   const result = await handleTool(toolName, args);
+  saveWorld();
 
   // Log the tool call
   logToolCall(toolName, args, result);
@@ -0,0 +1,15 @@
+pub struct Item {
+    pub sku: String,
+    pub unit_price_cents: u64,
+    pub quantity: u32,
+}
+
+impl Item {
+    pub fn new(sku: &str, unit_price_cents: u64, quantity: u32) -> Self {
+        Self {
+            sku: sku.to_string(),
+            unit_price_cents,
+            quantity,
+        }
+    }
+}
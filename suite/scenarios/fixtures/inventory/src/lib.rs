@@ -0,0 +1,32 @@
+mod item;
+
+pub use item::Item;
+
+/// A warehouse's stock
+#[derive(Default)]
+pub struct Inventory {
+    items: Vec<Item>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add stock; an existing SKU gets its quantity increased
+    pub fn add(&mut self, sku: &str, unit_price_cents: u64, quantity: u32) {
+        match self.items.iter_mut().find(|item| item.sku == sku) {
+            Some(item) => item.quantity += quantity,
+            None => self.items.push(Item::new(sku, unit_price_cents, quantity)),
+        }
+    }
+
+    pub fn quantity(&self, sku: &str) -> u32 {
+        self.items.iter().find(|item| item.sku == sku).map_or(0, |item| item.quantity)
+    }
+
+    /// Value of everything in stock, in cents
+    pub fn total_value_cents(&self) -> u64 {
+        self.items.iter().map(|item| item.unit_price_cents).sum()
+    }
+}
@@ -0,0 +1,22 @@
+use inventory::Inventory;
+
+#[test]
+fn empty_inventory_is_worth_nothing() {
+    assert_eq!(Inventory::new().total_value_cents(), 0);
+}
+
+#[test]
+fn adding_a_known_sku_increases_its_quantity() {
+    let mut inventory = Inventory::new();
+    inventory.add("BOLT-M6", 15, 100);
+    inventory.add("BOLT-M6", 15, 50);
+    assert_eq!(inventory.quantity("BOLT-M6"), 150);
+}
+
+#[test]
+fn total_value_counts_every_unit() {
+    let mut inventory = Inventory::new();
+    inventory.add("BOLT-M6", 15, 100);
+    inventory.add("NUT-M6", 5, 200);
+    assert_eq!(inventory.total_value_cents(), 15 * 100 + 5 * 200);
+}
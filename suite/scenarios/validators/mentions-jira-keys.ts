@@ -0,0 +1,27 @@
+import { existsSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import type { CustomValidator } from "../../src/types.js";
+
+/** workflow-log.md should mention every Jira issue key the agent created */
+export const validate: CustomValidator = ({ workdir, toolCalls }) => {
+  const keys = toolCalls
+    .filter((call) => call.tool === "jira_create_issue" && call.result?.key)
+    .map((call) => String(call.result.key));
+
+  if (keys.length === 0) {
+    return { passed: false, message: "No Jira issues were created" };
+  }
+
+  const logPath = join(workdir, "workflow-log.md");
+  if (!existsSync(logPath)) {
+    return { passed: false, message: "File not found: workflow-log.md" };
+  }
+
+  const summary = readFileSync(logPath, "utf-8");
+  const missing = keys.filter((key) => !summary.includes(key));
+  return {
+    passed: missing.length === 0,
+    message: missing.length ? `workflow-log.md does not mention: ${missing.join(", ")}` : undefined,
+    score: (keys.length - missing.length) / keys.length,
+  };
+};
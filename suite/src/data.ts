@@ -0,0 +1,328 @@
+import { readFileSync } from "node:fs";
+import { extname } from "node:path";
+import { parse as parseYaml } from "yaml";
+import { parseToml } from "./toml.js";
+
+// Structured data files for the data_path / data_schema rules: parsing,
+// JSONPath-like selectors and a JSON Schema checker.
+
+export type DataFormat = "json" | "yaml" | "toml";
+
+const DATA_FORMATS: Record<string, DataFormat> = { ".json": "json", ".yaml": "yaml", ".yml": "yaml", ".toml": "toml" };
+
+/** Parse a JSON/YAML/TOML file; the format comes from the extension unless given */
+export function loadDataFile(fullPath: string, format?: DataFormat): unknown {
+  const kind = format ?? DATA_FORMATS[extname(fullPath).toLowerCase()];
+  if (!kind) {
+    throw new Error(`Can't tell the format of ${fullPath} (set format: json, yaml or toml)`);
+  }
+  const content = readFileSync(fullPath, "utf-8");
+  switch (kind) {
+    case "json":
+      return JSON.parse(content);
+    case "yaml":
+      return parseYaml(content);
+    case "toml":
+      return parseToml(content);
+  }
+}
+
+export interface Selected {
+  path: string;   // concrete path, e.g. $.servers[1].port
+  value: unknown;
+}
+
+type Segment = { kind: "key"; key: string } | { kind: "index"; index: number } | { kind: "wildcard" } | { kind: "descend"; key: string };
+
+/** Tokenize $.a.b[0]['c d'][*].*..e */
+function parseSelector(selector: string): Segment[] {
+  const segments: Segment[] = [];
+  let rest = selector.trim().replace(/^\$/, "");
+  while (rest) {
+    let m: RegExpExecArray | null;
+    if ((m = /^\.\.([\w-]+|\*)/.exec(rest))) {
+      segments.push({ kind: "descend", key: m[1] });
+    } else if ((m = /^\.\*|^\[\*\]/.exec(rest))) {
+      segments.push({ kind: "wildcard" });
+    } else if ((m = /^\.([\w-]+)/.exec(rest))) {
+      segments.push({ kind: "key", key: m[1] });
+    } else if ((m = /^\[(-?\d+)\]/.exec(rest))) {
+      segments.push({ kind: "index", index: parseInt(m[1], 10) });
+    } else if ((m = /^\[(['"])(.*?)\1\]/.exec(rest))) {
+      segments.push({ kind: "key", key: m[2] });
+    } else if ((m = /^([\w-]+)/.exec(rest)) && segments.length === 0) {
+      segments.push({ kind: "key", key: m[1] });  // leading "a.b" without $.
+    } else {
+      throw new Error(`Invalid selector "${selector}" at "${rest}"`);
+    }
+    rest = rest.slice(m[0].length);
+  }
+  return segments;
+}
+
+function childPath(path: string, key: string | number): string {
+  if (typeof key === "number") return `${path}[${key}]`;
+  return /^[A-Za-z_][\w-]*$/.test(key) ? `${path}.${key}` : `${path}['${key}']`;
+}
+
+function children(item: Selected): Selected[] {
+  const { path, value } = item;
+  if (Array.isArray(value)) return value.map((v, i) => ({ path: childPath(path, i), value: v }));
+  if (value !== null && typeof value === "object") {
+    return Object.entries(value).map(([k, v]) => ({ path: childPath(path, k), value: v }));
+  }
+  return [];
+}
+
+function childByKey(item: Selected, key: string): Selected[] {
+  const { path, value } = item;
+  return value !== null && typeof value === "object" && !Array.isArray(value) && key in value
+    ? [{ path: childPath(path, key), value: (value as Record<string, unknown>)[key] }]
+    : [];
+}
+
+/** Every node below item (item included), depth first */
+function descendants(item: Selected): Selected[] {
+  return [item, ...children(item).flatMap(descendants)];
+}
+
+/**
+ * Values matching a JSONPath-like selector: `$.a.b`, `$.list[0]`, `$.list[-1]`,
+ * `$.list[*].name`, `$['odd key']`, `$..name` (any depth).
+ */
+export function select(data: unknown, selector: string): Selected[] {
+  let current: Selected[] = [{ path: "$", value: data }];
+  for (const segment of parseSelector(selector)) {
+    current = current.flatMap((item): Selected[] => {
+      const { path, value } = item;
+      switch (segment.kind) {
+        case "key":
+          return childByKey(item, segment.key);
+        case "index": {
+          if (!Array.isArray(value)) return [];
+          const index = segment.index < 0 ? value.length + segment.index : segment.index;
+          return index >= 0 && index < value.length ? [{ path: childPath(path, index), value: value[index] }] : [];
+        }
+        case "wildcard":
+          return children(item);
+        case "descend":
+          return descendants(item).flatMap((node) => (segment.key === "*" ? children(node) : childByKey(node, segment.key)));
+      }
+    });
+  }
+  return current;
+}
+
+export function deepEqual(a: unknown, b: unknown): boolean {
+  if (a === b) return true;
+  if (typeof a !== typeof b || a === null || b === null || typeof a !== "object") return false;
+  if (Array.isArray(a) !== Array.isArray(b)) return false;
+  const keysA = Object.keys(a as object);
+  const keysB = Object.keys(b as object);
+  return keysA.length === keysB.length &&
+    keysA.every((k) => deepEqual((a as Record<string, unknown>)[k], (b as Record<string, unknown>)[k]));
+}
+
+/** Short rendering of a value for failure messages */
+export function showValue(value: unknown): string {
+  const text = value === undefined ? "undefined" : JSON.stringify(value);
+  return text.length > 120 ? text.slice(0, 117) + "..." : text;
+}
+
+function typeOf(value: unknown): string {
+  if (value === null) return "null";
+  if (Array.isArray(value)) return "array";
+  if (typeof value === "number") return Number.isInteger(value) ? "integer" : "number";
+  return typeof value;
+}
+
+// Keywords checkSchema evaluates, plus annotations that assert nothing. Any
+// other keyword makes the data_schema rule fail up front, so a schema never
+// passes on a constraint that was silently ignored.
+const SCHEMA_KEYWORDS = new Set([
+  "type", "enum", "const",
+  "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf",
+  "minLength", "maxLength", "pattern", "format",
+  "items", "prefixItems", "additionalItems", "minItems", "maxItems", "uniqueItems", "contains", "minContains", "maxContains",
+  "properties", "required", "additionalProperties", "patternProperties", "propertyNames",
+  "minProperties", "maxProperties", "dependentRequired", "dependentSchemas", "dependencies",
+  "allOf", "anyOf", "oneOf", "not", "if", "then", "else", "$ref",
+  "$schema", "$id", "$comment", "$defs", "definitions", "title", "description", "default", "examples",
+  "readOnly", "writeOnly", "deprecated",
+]);
+
+const STRING_FORMATS: Record<string, (value: string) => boolean> = {
+  "date-time": (v) => /^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:[Zz]|[+-]\d{2}:\d{2})$/.test(v) && !isNaN(Date.parse(v)),
+  date: (v) => /^\d{4}-\d{2}-\d{2}$/.test(v) && !isNaN(Date.parse(v)),
+  time: (v) => /^\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:[Zz]|[+-]\d{2}:\d{2})?$/.test(v),
+  email: (v) => /^[^\s@]+@[^\s@]+\.[^\s@]+$/.test(v),
+  hostname: (v) => /^(?=.{1,253}$)[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)*$/.test(v),
+  ipv4: (v) => /^(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)$/.test(v),
+  ipv6: (v) => v.includes(":") && URL.canParse(`http://[${v}]`),
+  uri: (v) => /^[A-Za-z][A-Za-z0-9+.-]*:/.test(v) && URL.canParse(v),
+  uuid: (v) => /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i.test(v),
+};
+
+// Keywords whose value is a subschema, a list of them, or a map of them
+const SUBSCHEMA = new Set(["items", "additionalItems", "contains", "additionalProperties", "propertyNames", "not", "if", "then", "else"]);
+const SUBSCHEMA_LIST = new Set(["prefixItems", "allOf", "anyOf", "oneOf"]);
+const SUBSCHEMA_MAP = new Set(["properties", "patternProperties", "$defs", "definitions", "dependentSchemas", "dependencies"]);
+
+/**
+ * Keywords and formats anywhere in the schema that checkSchema can't evaluate,
+ * as "#/pointer" locations. Check before checkSchema: inside not/anyOf/if an
+ * ignored keyword would just read as a (non-)match.
+ */
+export function unsupportedKeywords(schema: any, pointer = "#"): string[] {
+  if (!schema || typeof schema !== "object" || Array.isArray(schema)) return [];
+  const found: string[] = [];
+  for (const [key, sub] of Object.entries(schema)) {
+    const at = `${pointer}/${key}`;
+    if (!SCHEMA_KEYWORDS.has(key)) {
+      found.push(at);
+    } else if (key === "format" && !Object.hasOwn(STRING_FORMATS, String(sub))) {
+      found.push(`${at} "${sub}"`);
+    } else if (SUBSCHEMA.has(key) || SUBSCHEMA_LIST.has(key)) {
+      [sub].flat().forEach((s, i) => found.push(...unsupportedKeywords(s, Array.isArray(sub) ? `${at}/${i}` : at)));
+    } else if (SUBSCHEMA_MAP.has(key) && sub && typeof sub === "object") {
+      for (const [name, s] of Object.entries(sub)) found.push(...unsupportedKeywords(s, `${at}/${name}`));
+    }
+  }
+  return found;
+}
+
+/**
+ * Check a value against a JSON Schema. Supports the usual validation keywords
+ * of drafts 7 to 2020-12 (type, enum, const, numeric and string limits,
+ * pattern, format, items/prefixItems, contains, properties and friends,
+ * required, dependentRequired, allOf/anyOf/oneOf/not, if/then/else) and local
+ * $ref ("#/definitions/..", "#/$defs/.."). Returns one "<path>: <problem>"
+ * line per violation. Keywords it doesn't know are skipped (see unsupportedKeywords).
+ */
+export function checkSchema(value: unknown, schema: any, root: any = schema, path = "$"): string[] {
+  if (schema === true || schema === undefined) return [];
+  if (schema === false) return [`${path}: not allowed`];
+
+
+  if (schema.$ref) {
+    const target = String(schema.$ref).replace(/^#\/?/, "").split("/").filter(Boolean)
+      .reduce((node: any, key: string) => node?.[decodeURIComponent(key.replace(/~1/g, "/").replace(/~0/g, "~"))], root);
+    if (target === undefined) return [`${path}: unresolved $ref ${schema.$ref}`];
+    return checkSchema(value, target, root, path);
+  }
+
+  const errors: string[] = [];
+  const actualType = typeOf(value);
+
+  if (schema.type) {
+    const types: string[] = [schema.type].flat();
+    const ok = types.some((t) => t === actualType || (t === "number" && actualType === "integer"));
+    if (!ok) return [`${path}: expected ${types.join(" or ")}, got ${actualType} ${showValue(value)}`];
+  }
+  if (schema.enum && !schema.enum.some((e: unknown) => deepEqual(e, value))) {
+    errors.push(`${path}: ${showValue(value)} is not one of ${showValue(schema.enum)}`);
+  }
+  if ("const" in schema && !deepEqual(schema.const, value)) {
+    errors.push(`${path}: expected ${showValue(schema.const)}, got ${showValue(value)}`);
+  }
+
+  if (typeof value === "number") {
+    if (schema.minimum !== undefined && value < schema.minimum) errors.push(`${path}: ${value} is below the minimum ${schema.minimum}`);
+    if (schema.maximum !== undefined && value > schema.maximum) errors.push(`${path}: ${value} is above the maximum ${schema.maximum}`);
+    if (typeof schema.exclusiveMinimum === "number" && value <= schema.exclusiveMinimum) errors.push(`${path}: ${value} must be > ${schema.exclusiveMinimum}`);
+    if (typeof schema.exclusiveMaximum === "number" && value >= schema.exclusiveMaximum) errors.push(`${path}: ${value} must be < ${schema.exclusiveMaximum}`);
+    if (schema.multipleOf && !Number.isInteger(value / schema.multipleOf)) errors.push(`${path}: ${value} is not a multiple of ${schema.multipleOf}`);
+  }
+
+  if (typeof value === "string") {
+    if (schema.minLength !== undefined && value.length < schema.minLength) errors.push(`${path}: shorter than ${schema.minLength} characters`);
+    if (schema.maxLength !== undefined && value.length > schema.maxLength) errors.push(`${path}: longer than ${schema.maxLength} characters`);
+    if (schema.pattern && !new RegExp(schema.pattern).test(value)) errors.push(`${path}: ${showValue(value)} doesn't match /${schema.pattern}/`);
+    if (schema.format && !STRING_FORMATS[schema.format](value)) errors.push(`${path}: ${showValue(value)} is not a valid ${schema.format}`);
+  }
+
+  if (Array.isArray(value)) {
+    if (schema.minItems !== undefined && value.length < schema.minItems) errors.push(`${path}: ${value.length} items, expected at least ${schema.minItems}`);
+    if (schema.maxItems !== undefined && value.length > schema.maxItems) errors.push(`${path}: ${value.length} items, expected at most ${schema.maxItems}`);
+    if (schema.uniqueItems && value.some((v, i) => value.findIndex((w) => deepEqual(v, w)) !== i)) errors.push(`${path}: items are not unique`);
+    // Positional schemas: prefixItems (2020-12) or an items array (draft 7)
+    const prefix: any[] = schema.prefixItems ?? (Array.isArray(schema.items) ? schema.items : []);
+    const rest = schema.prefixItems ? schema.items : Array.isArray(schema.items) ? schema.additionalItems : schema.items;
+    value.forEach((item, i) => {
+      const itemSchema = i < prefix.length ? prefix[i] : rest;
+      errors.push(...checkSchema(item, itemSchema, root, `${path}[${i}]`));
+    });
+    if (schema.contains !== undefined) {
+      const matches = value.filter((item, i) => checkSchema(item, schema.contains, root, `${path}[${i}]`).length === 0).length;
+      const min = schema.minContains ?? 1;
+      if (matches < min) errors.push(`${path}: ${matches} items match "contains", expected at least ${min}`);
+      if (schema.maxContains !== undefined && matches > schema.maxContains) errors.push(`${path}: ${matches} items match "contains", expected at most ${schema.maxContains}`);
+    }
+  }
+
+  if (actualType === "object") {
+    const object = value as Record<string, unknown>;
+    for (const key of schema.required ?? []) {
+      if (!(key in object)) errors.push(`${path}: missing required property "${key}"`);
+    }
+    const count = Object.keys(object).length;
+    if (schema.minProperties !== undefined && count < schema.minProperties) errors.push(`${path}: ${count} properties, expected at least ${schema.minProperties}`);
+    if (schema.maxProperties !== undefined && count > schema.maxProperties) errors.push(`${path}: ${count} properties, expected at most ${schema.maxProperties}`);
+    // dependentRequired / dependentSchemas, or draft 7's dependencies (either form)
+    const dependencies = { ...schema.dependencies, ...schema.dependentRequired, ...schema.dependentSchemas };
+    for (const [key, dependency] of Object.entries(dependencies)) {
+      if (!(key in object)) continue;
+      if (Array.isArray(dependency)) {
+        for (const needed of dependency) {
+          if (!(needed in object)) errors.push(`${path}: "${key}" requires property "${needed}"`);
+        }
+      } else {
+        errors.push(...checkSchema(value, dependency, root, path));
+      }
+    }
+    if (schema.propertyNames !== undefined) {
+      for (const key of Object.keys(object)) {
+        if (checkSchema(key, schema.propertyNames, root, path).length) errors.push(`${path}: property name "${key}" doesn't match "propertyNames"`);
+      }
+    }
+    const properties = schema.properties ?? {};
+    const patterns = Object.entries(schema.patternProperties ?? {});
+    for (const [key, child] of Object.entries(object)) {
+      const keyPath = childPath(path, key);
+      const matchingPatterns = patterns.filter(([pattern]) => new RegExp(pattern).test(key));
+      if (key in properties) {
+        errors.push(...checkSchema(child, properties[key], root, keyPath));
+      }
+      for (const [, patternSchema] of matchingPatterns) {
+        errors.push(...checkSchema(child, patternSchema, root, keyPath));
+      }
+      if (!(key in properties) && matchingPatterns.length === 0 && schema.additionalProperties !== undefined) {
+        if (schema.additionalProperties === false) {
+          errors.push(`${path}: unexpected property "${key}"`);
+        } else {
+          errors.push(...checkSchema(child, schema.additionalProperties, root, keyPath));
+        }
+      }
+    }
+  }
+
+  for (const sub of schema.allOf ?? []) {
+    errors.push(...checkSchema(value, sub, root, path));
+  }
+  if (schema.anyOf && !schema.anyOf.some((sub: any) => checkSchema(value, sub, root, path).length === 0)) {
+    errors.push(`${path}: matches none of anyOf`);
+  }
+  if (schema.oneOf) {
+    const matching = schema.oneOf.filter((sub: any) => checkSchema(value, sub, root, path).length === 0).length;
+    if (matching !== 1) errors.push(`${path}: matches ${matching} of oneOf, expected exactly 1`);
+  }
+  if (schema.not !== undefined && checkSchema(value, schema.not, root, path).length === 0) {
+    errors.push(`${path}: must not match the "not" schema`);
+  }
+  if (schema.if !== undefined) {
+    const branch = checkSchema(value, schema.if, root, path).length === 0 ? schema.then : schema.else;
+    errors.push(...checkSchema(value, branch, root, path));
+  }
+
+  return errors;
+}
@@ -0,0 +1,461 @@
+import { appendFileSync, copyFileSync, existsSync, mkdirSync, readFileSync, renameSync, writeFileSync } from "node:fs";
+import { execSync } from "node:child_process";
+import { randomBytes } from "node:crypto";
+import { basename, join } from "node:path";
+import type { ValidationRule } from "./types.js";
+import { validationLabel } from "./validator.js";
+
+// Run history: every suite run is appended to history/runs.jsonl (one JSON
+// object per line), and its report and logs are archived under history/<runId>/.
+
+// =============================================================================
+// Storage
+// =============================================================================
+
+const HISTORY_VERSION = 1;
+
+/** A result's cache inputs (CacheInputs in runner.ts) */
+export type InputHashes = Record<"scenarioHash" | "modelKey" | "runnerHash" | "binaryHash" | "mcpHarnessHash", string>;
+
+/** The parts of a results.json entry the history views read */
+export interface HistoryResult {
+  scenario: string;
+  model: { name: string; provider: string; model: string };
+  runner: { name: string; type: string };
+  status: string;
+  cached: boolean;
+  durationMs?: number;
+  toolCalls: number;
+  logFile: string;
+  attempts?: Array<{ logFile: string }>;
+  stats?: { n: number; passes: number };
+  validations: Array<{ rule: ValidationRule; passed: boolean; message?: string }>;
+  inputs: InputHashes;
+}
+
+export interface HistoryRun {
+  version: number;
+  runId: string;
+  startedAt: string;
+  finishedAt: string;
+  git: { sha: string | null; dirty: boolean };
+  argv: string[];
+  config: unknown;  // suite config after CLI filters
+  binaryHashes: Record<string, string>;
+  mcpHarnessHash: string;
+  summary: Record<string, number>;
+  results: HistoryResult[];
+}
+
+export function historyIndexPath(historyDir: string): string {
+  return join(historyDir, "runs.jsonl");
+}
+
+/**
+ * Sortable, filesystem-safe ID from the run's start time to the millisecond,
+ * plus a random suffix so runs started together don't share a record, e.g.
+ * 20260204-093012-481-7f3a
+ */
+export function newRunId(startedAt: Date): string {
+  const stamp = startedAt.toISOString().replace(/[-:]/g, "").replace("T", "-");
+  return `${stamp.slice(0, 15)}-${stamp.slice(16, 19)}-${randomBytes(2).toString("hex")}`;
+}
+
+export function gitInfo(dir: string): { sha: string | null; dirty: boolean } {
+  try {
+    const sha = execSync("git rev-parse HEAD", { cwd: dir, encoding: "utf-8", stdio: "pipe" }).trim();
+    const dirty = execSync("git status --porcelain", { cwd: dir, encoding: "utf-8", stdio: "pipe" }).trim() !== "";
+    return { sha, dirty };
+  } catch (e) {
+    return { sha: null, dirty: false };
+  }
+}
+
+/** Append the run to the index and archive its report and logs */
+export function recordRun(
+  historyDir: string,
+  run: Omit<HistoryRun, "version">,
+  logsDir: string,
+  reportPath: string
+): string {
+  const runDir = join(historyDir, run.runId);
+  const runLogsDir = join(runDir, "logs");
+  mkdirSync(runLogsDir, { recursive: true });
+
+  const logNames = new Set<string>();
+  for (const result of run.results) {
+    logNames.add(basename(result.logFile));
+    for (const attempt of result.attempts ?? []) {
+      logNames.add(basename(attempt.logFile));
+    }
+  }
+  for (const name of logNames) {
+    const source = join(logsDir, name);
+    if (existsSync(source)) {
+      copyFileSync(source, join(runLogsDir, name));
+    }
+  }
+  if (existsSync(reportPath)) {
+    copyFileSync(reportPath, join(runDir, "report.html"));
+  }
+
+  appendFileSync(historyIndexPath(historyDir), JSON.stringify({ version: HISTORY_VERSION, ...run }) + "\n");
+  return runDir;
+}
+
+/** All recorded runs, oldest first (unparseable lines are skipped) */
+export function loadHistory(historyDir: string): HistoryRun[] {
+  const indexPath = historyIndexPath(historyDir);
+  if (!existsSync(indexPath)) {
+    return [];
+  }
+  return readFileSync(indexPath, "utf-8")
+    .split("\n")
+    .filter((line) => line.trim())
+    .map((line) => {
+      try {
+        return JSON.parse(line) as HistoryRun;
+      } catch {
+        return null;
+      }
+    })
+    .filter((run): run is HistoryRun => run !== null);
+}
+
+// =============================================================================
+// Trends
+// =============================================================================
+
+/** Pass rate of one result: passes/attempts when known, else 1 or 0. Errors have none. */
+export function passRate(result: HistoryResult): number | undefined {
+  if (result.stats && result.stats.n > 0) return result.stats.passes / result.stats.n;
+  if (result.status === "passed") return 1;
+  if (result.status === "failed") return 0;
+  return undefined;
+}
+
+export function cellKey(result: HistoryResult): string {
+  return `${result.scenario} / ${result.model.name} / ${result.runner.name}`;
+}
+
+interface TrendPoint {
+  rate?: number;
+  changedInputs?: string[];  // cache inputs that differ from the group's previous run
+}
+
+interface TrendRow {
+  label: string;
+  points: TrendPoint[];  // one per run
+}
+
+function trendRows(
+  runs: HistoryRun[],
+  groupOf: (result: HistoryResult) => string,
+  trackInputs = false
+): TrendRow[] {
+  const labels = [...new Set(runs.flatMap((run) => run.results.map(groupOf)))].sort();
+  return labels.map((label) => {
+    let previousInputs: InputHashes | undefined;
+    const points = runs.map((run) => {
+      const results = run.results.filter((r) => groupOf(r) === label);
+      const rates = results.map(passRate).filter((r): r is number => r !== undefined);
+      const point: TrendPoint = {
+        rate: rates.length ? rates.reduce((a, b) => a + b, 0) / rates.length : undefined,
+      };
+      if (trackInputs && results.length === 1) {
+        const inputs = results[0].inputs;
+        if (previousInputs) {
+          const keys = Object.keys(inputs) as Array<keyof InputHashes>;
+          point.changedInputs = keys.filter((k) => inputs[k] !== previousInputs![k]);
+        }
+        previousInputs = inputs;
+      }
+      return point;
+    });
+    return { label, points };
+  });
+}
+
+function pct(rate: number | undefined): string {
+  return rate === undefined ? "—" : `${Math.round(rate * 100)}%`;
+}
+
+function runPassRate(run: HistoryRun): number | undefined {
+  const rates = run.results.map(passRate).filter((r): r is number => r !== undefined);
+  return rates.length ? rates.reduce((a, b) => a + b, 0) / rates.length : undefined;
+}
+
+export function printHistory(runs: HistoryRun[]): void {
+  console.log("\n" + "=".repeat(60));
+  console.log("HISTORY");
+  console.log("=".repeat(60));
+
+  for (const run of runs) {
+    const sha = run.git.sha ? `${run.git.sha.slice(0, 8)}${run.git.dirty ? "*" : ""}` : "no git";
+    const { passed = 0, total = 0 } = run.summary;
+    console.log(`${run.runId}  ${sha.padEnd(9)}  ${passed}/${total} passed  (${pct(runPassRate(run))})`);
+  }
+
+  console.log("\nPass rate by model (oldest → newest):");
+  for (const row of trendRows(runs, (r) => r.model.name)) {
+    console.log(`  ${row.label.padEnd(30)} ${row.points.map((p) => pct(p.rate).padStart(5)).join(" ")}`);
+  }
+}
+
+function heatmapTable(title: string, runs: HistoryRun[], rows: TrendRow[]): string {
+  const cell = (point: TrendPoint) => {
+    if (point.rate === undefined) return `<td class="rate none">—</td>`;
+    const changed = point.changedInputs?.length ? point.changedInputs : undefined;
+    const hue = Math.round(point.rate * 120);  // red → green
+    return `<td class="rate" style="background: hsl(${hue}, 55%, 22%)"${changed ? ` title="Changed since previous run: ${changed.join(", ")}"` : ""}>${changed ? '<span class="changed">◆</span>' : ""}${pct(point.rate)}</td>`;
+  };
+  return `
+  <h2>${title}</h2>
+  <table>
+    <thead><tr><th></th>${runs.map((run) => `<th title="${run.startedAt}${run.git.sha ? `\n${run.git.sha}${run.git.dirty ? " (dirty)" : ""}` : ""}">${run.runId}<br><span class="sha">${run.git.sha?.slice(0, 8) ?? ""}${run.git.dirty ? "*" : ""}</span></th>`).join("")}</tr></thead>
+    <tbody>
+      ${rows.map((row) => `<tr><td class="label">${row.label}</td>${row.points.map(cell).join("")}</tr>`).join("\n      ")}
+    </tbody>
+  </table>`;
+}
+
+/** Heatmaps of pass rate per model, runner, scenario and cell across runs */
+export function generateHistoryHtml(runs: HistoryRun[], outputPath: string): void {
+  const html = `<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="UTF-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1.0">
+  <title>History - Agent Gym Workout</title>
+  <style>
+    * { box-sizing: border-box; margin: 0; padding: 0; }
+    body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; background: #0d1117; color: #c9d1d9; padding: 2rem; }
+    h1 { color: #58a6ff; margin-bottom: 0.5rem; }
+    h2 { color: #58a6ff; font-size: 1.1rem; margin: 2rem 0 0.75rem; }
+    .summary { color: #8b949e; margin-bottom: 1rem; }
+    table { border-collapse: collapse; background: #161b22; border-radius: 8px; overflow: hidden; }
+    th, td { padding: 0.4rem 0.6rem; border-bottom: 1px solid #30363d; font-size: 0.8rem; }
+    th { background: #21262d; color: #58a6ff; font-weight: 600; white-space: nowrap; }
+    th .sha { color: #8b949e; font-weight: 400; font-family: monospace; }
+    td.label { white-space: nowrap; font-weight: 500; }
+    td.rate { text-align: center; min-width: 4rem; }
+    td.none { color: #484f58; }
+    .changed { color: #d29922; margin-right: 0.2rem; }
+    .timestamp { color: #6e7681; font-size: 0.9rem; margin-top: 2rem; }
+  </style>
+</head>
+<body>
+  <h1>Agent Gym History</h1>
+  <p class="summary">${runs.length} runs, oldest first. <span class="changed">◆</span> marks a cell whose cache inputs (scenario, binary, runner config, MCP harness) changed since its previous run; hover for details.</p>
+  ${heatmapTable("By model", runs, trendRows(runs, (r) => r.model.name))}
+  ${heatmapTable("By runner", runs, trendRows(runs, (r) => r.runner.name))}
+  ${heatmapTable("By scenario", runs, trendRows(runs, (r) => r.scenario))}
+  ${heatmapTable("By cell", runs, trendRows(runs, cellKey, true))}
+  <p class="timestamp">Generated: ${new Date().toISOString()}</p>
+</body>
+</html>`;
+
+  writeFileSync(`${outputPath}.tmp`, html);
+  renameSync(`${outputPath}.tmp`, outputPath);
+  console.log(`\n📈 History report saved to: ${outputPath}`);
+}
+
+// =============================================================================
+// Run Comparison
+// =============================================================================
+
+/** Find a run by ID, unique ID prefix, or "latest" / "previous" */
+export function findRun(runs: HistoryRun[], ref: string): HistoryRun {
+  if (ref === "latest" && runs.length > 0) return runs[runs.length - 1];
+  if (ref === "previous" && runs.length > 1) return runs[runs.length - 2];
+  const matches = runs.filter((run) => run.runId.startsWith(ref));
+  if (matches.length === 1) return matches[0];
+  throw new Error(matches.length
+    ? `Run "${ref}" is ambiguous: ${matches.map((r) => r.runId).join(", ")}`
+    : `No run "${ref}" in history (${runs.length} runs recorded)`);
+}
+
+interface CellDiff {
+  cell: string;
+  before?: HistoryResult;
+  after?: HistoryResult;
+  change: "regression" | "fix" | "status" | "validations" | "same" | "added" | "removed";
+  validations: Array<{ label: string; before?: boolean; after?: boolean }>;
+  changedInputs: string[];
+  durationDeltaMs?: number;
+  toolCallsDelta?: number;
+}
+
+export interface RunComparison {
+  before: HistoryRun;
+  after: HistoryRun;
+  cells: CellDiff[];
+}
+
+function validationMap(result?: HistoryResult): Map<string, boolean> {
+  const map = new Map<string, boolean>();
+  for (const v of result?.validations ?? []) {
+    // Disambiguate repeated labels (e.g. two file_exists on the same path in different turns)
+    let label = validationLabel(v.rule);
+    for (let i = 2; map.has(label); i++) label = `${validationLabel(v.rule)} #${i}`;
+    map.set(label, v.passed);
+  }
+  return map;
+}
+
+export function compareRuns(before: HistoryRun, after: HistoryRun): RunComparison {
+  const beforeCells = new Map(before.results.map((r) => [cellKey(r), r]));
+  const afterCells = new Map(after.results.map((r) => [cellKey(r), r]));
+  const keys = [...new Set([...beforeCells.keys(), ...afterCells.keys()])].sort();
+
+  const cells = keys.map((cell): CellDiff => {
+    const a = beforeCells.get(cell);
+    const b = afterCells.get(cell);
+
+    const va = validationMap(a);
+    const vb = validationMap(b);
+    const validations = [...new Set([...va.keys(), ...vb.keys()])]
+      .map((label) => ({ label, before: va.get(label), after: vb.get(label) }))
+      .filter((v) => v.before !== v.after);
+
+    let change: CellDiff["change"];
+    if (!a) change = "added";
+    else if (!b) change = "removed";
+    else if (a.status === "passed" && b.status === "failed") change = "regression";
+    else if (a.status === "failed" && b.status === "passed") change = "fix";
+    else if (a.status !== b.status) change = "status";
+    else if (validations.length) change = "validations";
+    else change = "same";
+
+    const keysOf = (r: HistoryResult) => Object.keys(r.inputs) as Array<keyof InputHashes>;
+    return {
+      cell,
+      before: a,
+      after: b,
+      change,
+      validations,
+      changedInputs: a && b ? keysOf(b).filter((k) => a.inputs[k] !== b.inputs[k]) : [],
+      durationDeltaMs: a?.durationMs !== undefined && b?.durationMs !== undefined ? b.durationMs - a.durationMs : undefined,
+      toolCallsDelta: a && b ? b.toolCalls - a.toolCalls : undefined,
+    };
+  });
+
+  return { before, after, cells };
+}
+
+function signed(n: number | undefined, unit = ""): string {
+  if (n === undefined) return "—";
+  return `${n > 0 ? "+" : ""}${n}${unit}`;
+}
+
+function secondsDelta(ms: number | undefined): string {
+  return ms === undefined ? "—" : signed(Math.round(ms / 100) / 10, "s");
+}
+
+export function printComparison({ before, after, cells }: RunComparison): void {
+  console.log("\n" + "=".repeat(60));
+  console.log(`COMPARE ${before.runId} → ${after.runId}`);
+  console.log("=".repeat(60));
+
+  const sections: Array<[string, CellDiff["change"]]> = [
+    ["Regressions (pass → fail)", "regression"],
+    ["Fixes (fail → pass)", "fix"],
+    ["Other status changes", "status"],
+    ["Validation changes", "validations"],
+    ["Only in " + after.runId, "added"],
+    ["Only in " + before.runId, "removed"],
+  ];
+  for (const [title, change] of sections) {
+    const matching = cells.filter((c) => c.change === change);
+    if (!matching.length) continue;
+    console.log(`\n${title}:`);
+    for (const c of matching) {
+      const status = c.before && c.after && c.before.status !== c.after.status ? ` (${c.before.status} → ${c.after.status})` : "";
+      const inputs = c.changedInputs.length ? ` [changed: ${c.changedInputs.join(", ")}]` : "";
+      console.log(`  ${c.cell}${status}${inputs}`);
+      for (const v of c.validations) {
+        console.log(`      ${v.before === undefined ? "·" : v.before ? "✓" : "✗"} → ${v.after === undefined ? "·" : v.after ? "✓" : "✗"} ${v.label}`);
+      }
+    }
+  }
+
+  const regressions = cells.filter((c) => c.change === "regression").length;
+  const fixes = cells.filter((c) => c.change === "fix").length;
+  console.log(`\n${regressions} regressions, ${fixes} fixes, ${cells.filter((c) => c.change === "same").length} unchanged`);
+}
+
+export function generateComparisonHtml({ before, after, cells }: RunComparison, outputPath: string): void {
+  const mark = (passed?: boolean) => (passed === undefined ? "·" : passed ? "✓" : "✗");
+  const changeLabel: Record<CellDiff["change"], string> = {
+    regression: "regression",
+    fix: "fix",
+    status: "status changed",
+    validations: "validations changed",
+    same: "unchanged",
+    added: "new",
+    removed: "removed",
+  };
+  const order: CellDiff["change"][] = ["regression", "fix", "status", "validations", "added", "removed", "same"];
+  const sorted = [...cells].sort((a, b) => order.indexOf(a.change) - order.indexOf(b.change));
+  const count = (change: CellDiff["change"]) => cells.filter((c) => c.change === change).length;
+  const runLabel = (run: HistoryRun) => `${run.runId}${run.git.sha ? ` (${run.git.sha.slice(0, 8)}${run.git.dirty ? "*" : ""})` : ""}`;
+
+  const html = `<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="UTF-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1.0">
+  <title>Compare ${before.runId} → ${after.runId} - Agent Gym Workout</title>
+  <style>
+    * { box-sizing: border-box; margin: 0; padding: 0; }
+    body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; background: #0d1117; color: #c9d1d9; padding: 2rem; }
+    h1 { color: #58a6ff; margin-bottom: 0.5rem; }
+    .summary { color: #8b949e; margin-bottom: 1.5rem; }
+    .summary .regression { color: #f85149; }
+    .summary .fix { color: #3fb950; }
+    table { width: 100%; border-collapse: collapse; background: #161b22; border-radius: 8px; overflow: hidden; }
+    th, td { padding: 0.5rem 0.75rem; text-align: left; border-bottom: 1px solid #30363d; font-size: 0.85rem; vertical-align: top; }
+    th { background: #21262d; color: #58a6ff; font-weight: 600; }
+    tr.regression td:first-child { border-left: 3px solid #f85149; }
+    tr.fix td:first-child { border-left: 3px solid #3fb950; }
+    tr.status td:first-child, tr.validations td:first-child { border-left: 3px solid #d29922; }
+    tr.same { color: #8b949e; }
+    .change { font-size: 0.75rem; padding: 0.1rem 0.35rem; border-radius: 3px; background: #30363d; white-space: nowrap; }
+    tr.regression .change { background: #f8514933; color: #f85149; }
+    tr.fix .change { background: #3fb95033; color: #3fb950; }
+    .validation { font-size: 0.8rem; }
+    .inputs { color: #d29922; font-size: 0.8rem; }
+    .timestamp { color: #6e7681; font-size: 0.9rem; margin-top: 2rem; }
+  </style>
+</head>
+<body>
+  <h1>${runLabel(before)} → ${runLabel(after)}</h1>
+  <p class="summary">
+    <span class="regression">${count("regression")} regressions</span> /
+    <span class="fix">${count("fix")} fixes</span> /
+    ${count("status")} other status changes / ${count("validations")} validation changes / ${count("same")} unchanged
+  </p>
+  <table>
+    <thead>
+      <tr><th>Cell</th><th>Change</th><th>Status</th><th>Validations</th><th>Duration Δ</th><th>Tool calls Δ</th><th>Changed inputs</th></tr>
+    </thead>
+    <tbody>
+      ${sorted.map((c) => `<tr class="${c.change}">
+        <td>${c.cell}</td>
+        <td><span class="change">${changeLabel[c.change]}</span></td>
+        <td>${c.before?.status ?? "—"} → ${c.after?.status ?? "—"}</td>
+        <td>${c.validations.map((v) => `<div class="validation">${mark(v.before)} → ${mark(v.after)} ${v.label}</div>`).join("")}</td>
+        <td>${secondsDelta(c.durationDeltaMs)}</td>
+        <td>${signed(c.toolCallsDelta)}</td>
+        <td class="inputs">${c.changedInputs.join(", ")}</td>
+      </tr>`).join("\n      ")}
+    </tbody>
+  </table>
+  <p class="timestamp">Generated: ${new Date().toISOString()}</p>
+</body>
+</html>`;
+
+  writeFileSync(`${outputPath}.tmp`, html);
+  renameSync(`${outputPath}.tmp`, outputPath);
+  console.log(`\n📊 Comparison saved to: ${outputPath}`);
+}
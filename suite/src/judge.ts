@@ -0,0 +1,95 @@
+import { createHash } from "node:crypto";
+import { existsSync, mkdirSync, readFileSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+// Grader model for the `judge` rule: sends a rubric plus what the agent
+// produced to any OpenAI-compatible chat completions endpoint and reads back a
+// score and rationale. Verdicts are cached by (model, rubric, input), so an
+// unchanged file or transcript is never graded twice.
+
+export interface JudgeConfig {
+  url: string;           // API root including /v1, e.g. http://localhost:11434/v1
+  model: string;
+  api_key_env?: string;  // env var holding the API key, if the endpoint needs one
+  timeout?: number;      // seconds per request (default 120)
+}
+
+export interface Verdict {
+  score: number;      // 0..1
+  rationale: string;
+  cached?: boolean;
+}
+
+const JUDGE_CACHE_DIR = join(import.meta.dirname, "../.cache/judge");
+const DEFAULT_TIMEOUT_SECONDS = 120;
+// Longer inputs keep their last part, where transcripts end up
+const MAX_INPUT_CHARS = 60_000;
+
+const SYSTEM_PROMPT = `You grade the work of an AI agent against a rubric.
+Read the rubric and the material, then reply with a single JSON object and nothing else:
+{"score": <number from 0 to 1>, "rationale": "<one to three sentences>"}
+1 means the rubric is fully met, 0 means not at all.`;
+
+function truncate(input: string): string {
+  return input.length > MAX_INPUT_CHARS
+    ? `[... ${input.length - MAX_INPUT_CHARS} earlier characters omitted]\n${input.slice(-MAX_INPUT_CHARS)}`
+    : input;
+}
+
+/** Pull {"score", "rationale"} out of a reply, tolerating code fences and chatter around it */
+function parseVerdict(reply: string): Verdict {
+  const json = reply.slice(reply.indexOf("{"), reply.lastIndexOf("}") + 1);
+  let parsed: any;
+  try {
+    parsed = JSON.parse(json);
+  } catch {
+    throw new Error(`Judge reply isn't JSON: ${reply.slice(0, 200)}`);
+  }
+  const score = Number(parsed.score);
+  if (!Number.isFinite(score)) {
+    throw new Error(`Judge reply has no numeric score: ${reply.slice(0, 200)}`);
+  }
+  return { score: Math.min(1, Math.max(0, score)), rationale: String(parsed.rationale ?? "").trim() };
+}
+
+/**
+ * Grade input (labelled e.g. "File workflow-log.md" or "Transcript") against
+ * the rubric. Throws when the grader can't be reached or replies nonsense.
+ */
+export async function judge(config: JudgeConfig, rubric: string, label: string, input: string): Promise<Verdict> {
+  const material = truncate(input);
+  const key = createHash("sha256")
+    .update(JSON.stringify({ model: config.model, system: SYSTEM_PROMPT, rubric, label, material }))
+    .digest("hex");
+  const cachePath = join(JUDGE_CACHE_DIR, `${key}.json`);
+  if (existsSync(cachePath)) {
+    return { ...(JSON.parse(readFileSync(cachePath, "utf-8")) as Verdict), cached: true };
+  }
+
+  const apiKey = config.api_key_env ? process.env[config.api_key_env] : undefined;
+  const response = await fetch(`${config.url.replace(/\/$/, "")}/chat/completions`, {
+    method: "POST",
+    headers: {
+      "content-type": "application/json",
+      ...(apiKey ? { authorization: `Bearer ${apiKey}` } : {}),
+    },
+    body: JSON.stringify({
+      model: config.model,
+      temperature: 0,
+      messages: [
+        { role: "system", content: SYSTEM_PROMPT },
+        { role: "user", content: `Rubric:\n${rubric.trim()}\n\n${label}:\n${material}` },
+      ],
+    }),
+    signal: AbortSignal.timeout((config.timeout ?? DEFAULT_TIMEOUT_SECONDS) * 1000),
+  });
+  if (!response.ok) {
+    throw new Error(`Judge request failed: ${response.status} ${(await response.text()).slice(0, 200)}`);
+  }
+  const body: any = await response.json();
+  const verdict = parseVerdict(String(body.choices?.[0]?.message?.content ?? ""));
+
+  mkdirSync(JUDGE_CACHE_DIR, { recursive: true });
+  writeFileSync(cachePath, JSON.stringify(verdict, null, 2));
+  return verdict;
+}
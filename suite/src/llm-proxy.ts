@@ -0,0 +1,237 @@
+import { createServer, type IncomingMessage, type ServerResponse } from "node:http";
+import type { AddressInfo } from "node:net";
+import { createHash } from "node:crypto";
+import { appendFileSync, existsSync, mkdirSync, readFileSync, writeFileSync } from "node:fs";
+import { join } from "node:path";
+
+// Record/replay proxy for model traffic. Agents talk to
+//   http://127.0.0.1:<port>/<testId>/<provider>/<upstream path>
+// In record mode requests are forwarded to the provider and every exchange is
+// appended to .recordings/<testId>/attempt<N>.jsonl; in replay mode the
+// recorded responses are served back without touching the network.
+
+export type ProxyMode = "record" | "replay";
+
+/** Where each provider's API lives (roots, without /v1) */
+export const DEFAULT_UPSTREAMS: Record<string, string> = {
+  ollama: "http://localhost:11434",
+  anthropic: "https://api.anthropic.com",
+  openai: "https://api.openai.com",
+};
+
+/** One recorded request/response. Request headers (API keys) are never stored. */
+interface Exchange {
+  seq: number;
+  method: string;
+  path: string;
+  requestHash: string;
+  request: unknown;
+  status: number;
+  contentType: string;
+  body: string;
+}
+
+interface Cassette {
+  file: string;
+  exchanges: Exchange[];
+  used: Set<number>;  // replay: exchanges already served
+}
+
+export interface LlmProxy {
+  mode: ProxyMode;
+  url: string;
+  /** Point the proxy at this pair's recording for the given attempt */
+  begin(testId: string, attempt: number): void;
+  /** Provider root URL an agent should use instead of the real one */
+  baseFor(testId: string, provider: string): string;
+  /**
+   * HTTP status of this pair's latest model response since begin() (0 = the
+   * proxy got none: upstream unreachable or nothing left to replay)
+   */
+  lastStatus(testId: string): number | undefined;
+  close(): Promise<void>;
+}
+
+function sha256(data: string): string {
+  return createHash("sha256").update(data).digest("hex").slice(0, 16);
+}
+
+function readBody(req: IncomingMessage): Promise<string> {
+  return new Promise((resolve, reject) => {
+    let body = "";
+    req.setEncoding("utf-8");
+    req.on("data", (chunk: string) => (body += chunk));
+    req.on("end", () => resolve(body));
+    req.on("error", reject);
+  });
+}
+
+function sendError(res: ServerResponse, status: number, message: string): void {
+  if (!res.headersSent) {
+    res.writeHead(status, { "content-type": "application/json" });
+  }
+  res.end(JSON.stringify({ error: { type: "llm_proxy_error", message } }));
+}
+
+function cassettePath(recordingsDir: string, testId: string, attempt: number): string {
+  return join(recordingsDir, testId, `attempt${attempt}.jsonl`);
+}
+
+export async function startLlmProxy(
+  mode: ProxyMode,
+  recordingsDir: string,
+  upstreams: Record<string, string>
+): Promise<LlmProxy> {
+  const cassettes = new Map<string, Cassette>();
+  const lastStatuses = new Map<string, number>();
+
+  async function forward(
+    req: IncomingMessage,
+    res: ServerResponse,
+    provider: string,
+    path: string,
+    body: string
+  ): Promise<Omit<Exchange, "seq" | "method" | "path" | "requestHash" | "request">> {
+    const upstream = upstreams[provider];
+    if (!upstream) {
+      throw new Error(`No upstream for provider "${provider}"`);
+    }
+
+    const headers: Record<string, string> = {};
+    for (const [name, value] of Object.entries(req.headers)) {
+      // fetch sets these itself (and would otherwise hand us compressed bytes)
+      if (value === undefined || ["host", "content-length", "accept-encoding", "connection"].includes(name)) continue;
+      headers[name] = Array.isArray(value) ? value.join(", ") : value;
+    }
+
+    const response = await fetch(upstream.replace(/\/$/, "") + path, {
+      method: req.method,
+      headers,
+      body: req.method === "GET" || req.method === "HEAD" ? undefined : body,
+    });
+
+    // Stream through (SSE responses stay incremental) while keeping a copy
+    const contentType = response.headers.get("content-type") ?? "application/json";
+    res.writeHead(response.status, { "content-type": contentType });
+    let text = "";
+    if (response.body) {
+      const decoder = new TextDecoder();
+      for await (const chunk of response.body) {
+        const piece = decoder.decode(chunk, { stream: true });
+        text += piece;
+        res.write(piece);
+      }
+      text += decoder.decode();
+    }
+    res.end();
+    return { status: response.status, contentType, body: text };
+  }
+
+  function replay(res: ServerResponse, testId: string, cassette: Cassette | undefined, requestHash: string): void {
+    lastStatuses.set(testId, 0);
+    if (!cassette) {
+      sendError(res, 404, `No recording for ${testId}`);
+      return;
+    }
+    const unused = (i: number) => !cassette.used.has(i);
+    // Prefer the identical request; otherwise serve the next exchange in order
+    // (the request can drift when e.g. the harness returns different tool results)
+    let index = cassette.exchanges.findIndex((e, i) => unused(i) && e.requestHash === requestHash);
+    if (index === -1) {
+      index = cassette.exchanges.findIndex((_, i) => unused(i));
+      if (index !== -1) {
+        console.log(`  Replay: request differs from recording, serving exchange #${cassette.exchanges[index].seq} in order`);
+      }
+    }
+    if (index === -1) {
+      sendError(res, 404, `Recording for ${testId} has no more exchanges (${cassette.exchanges.length} recorded)`);
+      return;
+    }
+    cassette.used.add(index);
+    const exchange = cassette.exchanges[index];
+    lastStatuses.set(testId, exchange.status);
+    res.writeHead(exchange.status, { "content-type": exchange.contentType });
+    res.end(exchange.body);
+  }
+
+  async function handle(req: IncomingMessage, res: ServerResponse): Promise<void> {
+    const url = new URL(req.url ?? "/", "http://proxy");
+    const [, rawTestId, provider, ...rest] = url.pathname.split("/");
+    const testId = decodeURIComponent(rawTestId ?? "");
+    const path = "/" + rest.join("/") + url.search;
+    const method = req.method ?? "GET";
+    const body = await readBody(req);
+    const requestHash = sha256(`${method} ${path}\n${body}`);
+    const cassette = cassettes.get(testId);
+
+    if (mode === "replay") {
+      replay(res, testId, cassette, requestHash);
+      return;
+    }
+
+    lastStatuses.set(testId, 0);
+    const response = await forward(req, res, provider, path, body);
+    lastStatuses.set(testId, response.status);
+    if (cassette) {
+      let request: unknown = body;
+      try {
+        request = JSON.parse(body);
+      } catch (e) { /* keep the raw body */ }
+      const exchange: Exchange = {
+        seq: cassette.exchanges.length + 1,
+        method,
+        path,
+        requestHash,
+        request,
+        ...response,
+      };
+      cassette.exchanges.push(exchange);
+      appendFileSync(cassette.file, JSON.stringify(exchange) + "\n");
+    }
+  }
+
+  const server = createServer((req, res) => {
+    handle(req, res).catch((err) => sendError(res, 502, `LLM proxy: ${err instanceof Error ? err.message : String(err)}`));
+  });
+  await new Promise<void>((resolve) => server.listen(0, "127.0.0.1", resolve));
+  server.unref();  // never keep the suite alive on its own
+
+  const url = `http://127.0.0.1:${(server.address() as AddressInfo).port}`;
+
+  return {
+    mode,
+    url,
+    begin(testId, attempt) {
+      lastStatuses.delete(testId);
+      if (mode === "record") {
+        const file = cassettePath(recordingsDir, testId, attempt);
+        mkdirSync(join(recordingsDir, testId), { recursive: true });
+        writeFileSync(file, "");
+        cassettes.set(testId, { file, exchanges: [], used: new Set() });
+        return;
+      }
+
+      // Replay this attempt's recording, or attempt 1's if there isn't one
+      const file = [attempt, 1].map((n) => cassettePath(recordingsDir, testId, n)).find(existsSync);
+      if (!file) {
+        console.log(`  Replay: no recording for ${testId}, model requests will fail`);
+        cassettes.delete(testId);
+        return;
+      }
+      const exchanges = readFileSync(file, "utf-8")
+        .split("\n")
+        .filter((line) => line.trim())
+        .map((line) => JSON.parse(line) as Exchange);
+      cassettes.set(testId, { file, exchanges, used: new Set() });
+    },
+    baseFor(testId, provider) {
+      return `${url}/${encodeURIComponent(testId)}/${provider}`;
+    },
+    lastStatus(testId) {
+      return lastStatuses.get(testId);
+    },
+    close() {
+      return new Promise((resolve) => server.close(() => resolve()));
+    },
+  };
+}
@@ -0,0 +1,197 @@
+import { createServer, type IncomingMessage, type ServerResponse } from "node:http";
+import type { AddressInfo } from "node:net";
+import type { MockStep } from "./types.js";
+
+// Scripted stand-in for a model (provider: mock). Serves the OpenAI-compatible
+// chat completions API at
+//   http://127.0.0.1:<port>/<testId>/v1/chat/completions
+// and answers each request with the next step of the scenario's `mock` script,
+// so the whole suite can run end to end without a real model.
+
+export interface MockLlm {
+  url: string;
+  /** Load the script for the next agent invocation of this pair */
+  begin(testId: string, steps: MockStep[]): void;
+  /** API root (without /v1) for an agent running this pair */
+  baseFor(testId: string): string;
+  close(): Promise<void>;
+}
+
+// Replies once the script runs out, and for requests that offer no tools
+// (title generation and the like), which never consume a step
+const FALLBACK_STEP: MockStep = { content: "Done." };
+
+interface Script {
+  steps: MockStep[];
+  next: number;
+}
+
+interface OpenAiToolCall {
+  id: string;
+  type: "function";
+  function: { name: string; arguments: string };
+}
+
+function readBody(req: IncomingMessage): Promise<string> {
+  return new Promise((resolve, reject) => {
+    let body = "";
+    req.setEncoding("utf-8");
+    req.on("data", (chunk: string) => (body += chunk));
+    req.on("end", () => resolve(body));
+    req.on("error", reject);
+  });
+}
+
+/**
+ * Scripts name tools without the agent's prefix ("slack_send_message" rather
+ * than goose's "harness__slack_send_message"), so match on the suffix.
+ */
+function resolveToolName(name: string, available: string[]): string | undefined {
+  if (available.includes(name)) return name;
+  return available.find((t) => /[_.:/-]$/.test(t.slice(0, -name.length)) && t.endsWith(name));
+}
+
+/**
+ * The call an agent offering these tools understands. Pi's MCP adapter in
+ * proxy mode only offers a single `mcp` tool that takes the real tool's name
+ * and its arguments (a JSON string in the adapter's schema), so a scripted
+ * harness call is wrapped in that.
+ */
+function toolCallFor(
+  call: { name: string; arguments?: Record<string, unknown> },
+  tools: Map<string, any>
+): { name: string; arguments: Record<string, unknown> } {
+  const args = call.arguments ?? {};
+  const name = resolveToolName(call.name, [...tools.keys()]);
+  if (name || !tools.has("mcp")) {
+    return { name: name ?? call.name, arguments: args };
+  }
+  const argsSchema = tools.get("mcp")?.properties?.args;
+  return {
+    name: "mcp",
+    arguments: { tool: call.name, args: argsSchema?.type === "string" ? JSON.stringify(args) : args },
+  };
+}
+
+/** Rough token count for the usage block (agents only display it) */
+function estimateTokens(text: string): number {
+  return Math.ceil(text.length / 4);
+}
+
+function sendJson(res: ServerResponse, status: number, body: unknown): void {
+  res.writeHead(status, { "content-type": "application/json" });
+  res.end(JSON.stringify(body));
+}
+
+export async function startMockLlm(): Promise<MockLlm> {
+  const scripts = new Map<string, Script>();
+  let counter = 0;
+
+  function complete(res: ServerResponse, testId: string, request: any, rawBody: string): void {
+    // Tool name -> parameter schema
+    const tools = new Map<string, any>();
+    for (const t of request.tools ?? []) {
+      const name = t.function?.name ?? t.name;
+      if (name) tools.set(name, t.function?.parameters ?? t.parameters);
+    }
+    const script = scripts.get(testId);
+    const step = tools.size > 0 && script && script.next < script.steps.length
+      ? script.steps[script.next++]
+      : FALLBACK_STEP;
+
+    const id = `mock-${++counter}`;
+    const model = request.model ?? "mock";
+    const created = Math.floor(Date.now() / 1000);
+    const toolCalls: OpenAiToolCall[] = (step.tool_calls ?? []).map((call, i) => {
+      const resolved = toolCallFor(call, tools);
+      return {
+        id: `call_${counter}_${i}`,
+        type: "function",
+        function: { name: resolved.name, arguments: JSON.stringify(resolved.arguments) },
+      };
+    });
+    const content = step.content ?? (toolCalls.length ? null : "");
+    const finishReason = toolCalls.length ? "tool_calls" : "stop";
+    const completionTokens = estimateTokens((content ?? "") + JSON.stringify(toolCalls));
+    const usage = {
+      prompt_tokens: estimateTokens(rawBody),
+      completion_tokens: completionTokens,
+      total_tokens: estimateTokens(rawBody) + completionTokens,
+    };
+
+    if (!request.stream) {
+      sendJson(res, 200, {
+        id,
+        object: "chat.completion",
+        created,
+        model,
+        choices: [{
+          index: 0,
+          message: { role: "assistant", content, ...(toolCalls.length ? { tool_calls: toolCalls } : {}) },
+          finish_reason: finishReason,
+        }],
+        usage,
+      });
+      return;
+    }
+
+    // Streaming: the whole reply in a handful of chunks
+    res.writeHead(200, { "content-type": "text/event-stream", "cache-control": "no-cache" });
+    const chunk = (delta: object, finish: string | null = null) =>
+      res.write(`data: ${JSON.stringify({
+        id,
+        object: "chat.completion.chunk",
+        created,
+        model,
+        choices: [{ index: 0, delta, finish_reason: finish }],
+      })}\n\n`);
+    chunk({ role: "assistant", content: content ?? "" });
+    toolCalls.forEach((call, index) => chunk({ tool_calls: [{ index, ...call }] }));
+    chunk({}, finishReason);
+    if (request.stream_options?.include_usage) {
+      res.write(`data: ${JSON.stringify({ id, object: "chat.completion.chunk", created, model, choices: [], usage })}\n\n`);
+    }
+    res.end("data: [DONE]\n\n");
+  }
+
+  async function handle(req: IncomingMessage, res: ServerResponse): Promise<void> {
+    const url = new URL(req.url ?? "/", "http://mock");
+    const [, rawTestId, ...rest] = url.pathname.split("/");
+    const testId = decodeURIComponent(rawTestId ?? "");
+    const path = "/" + rest.join("/");
+    const body = await readBody(req);
+
+    if (req.method === "GET" && path.endsWith("/models")) {
+      sendJson(res, 200, { object: "list", data: [{ id: "mock", object: "model", owned_by: "mock" }] });
+      return;
+    }
+    if (req.method === "POST" && path.endsWith("/chat/completions")) {
+      complete(res, testId, JSON.parse(body || "{}"), body);
+      return;
+    }
+    sendJson(res, 404, { error: { type: "mock_error", message: `Mock provider doesn't serve ${req.method} ${path}` } });
+  }
+
+  const server = createServer((req, res) => {
+    handle(req, res).catch((err) => sendJson(res, 500, {
+      error: { type: "mock_error", message: err instanceof Error ? err.message : String(err) },
+    }));
+  });
+  await new Promise<void>((resolve) => server.listen(0, "127.0.0.1", resolve));
+  server.unref();
+
+  const url = `http://127.0.0.1:${(server.address() as AddressInfo).port}`;
+
+  return {
+    url,
+    begin(testId, steps) {
+      scripts.set(testId, { steps, next: 0 });
+    },
+    baseFor(testId) {
+      return `${url}/${encodeURIComponent(testId)}`;
+    },
+    close() {
+      return new Promise((resolve) => server.close(() => resolve()));
+    },
+  };
+}
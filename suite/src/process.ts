@@ -0,0 +1,126 @@
+
+import { spawn } from "node:child_process";
+
+// Process management for agents and validation commands: each runs in its own
+// process group so a timeout or Ctrl-C also takes down whatever it spawned
+// (MCP servers, language servers, shells, build daemons).
+
+export const DEFAULT_TIMEOUT_SECONDS = 300;
+const KILL_GRACE_MS = 5000;  // SIGTERM -> SIGKILL
+
+export interface ProcessOptions {
+  timeoutMs: number;
+  onOutput?: (chunk: string) => void;  // stdout and stderr, as they arrive
+  name?: string;                       // for the timeout message (default "agent")
+}
+
+export interface ProcessResult {
+  output: string;  // stdout and stderr interleaved
+  stdout: string;
+  stderr: string;  // stderr alone: for an agent, the CLI's own diagnostics
+  exitCode: number | null;
+  signal: NodeJS.Signals | null;
+  timedOut: boolean;
+}
+
+// Process groups still alive, killed on Ctrl-C
+const activeGroups = new Set<number>();
+
+function killGroup(pid: number, signal: NodeJS.Signals): void {
+  try {
+    process.kill(-pid, signal);
+  } catch (e) {
+    // Group already gone
+  }
+}
+
+/**
+ * Spawn a process and stream its output. Resolves once the process and its
+ * group are gone; never rejects for a non-zero exit or a timeout.
+ */
+export function runProcess(
+  command: string,
+  args: string[],
+  spawnOptions: { cwd: string; env: NodeJS.ProcessEnv; shell?: string; stdin?: string },
+  options: ProcessOptions
+): Promise<ProcessResult> {
+  return new Promise((resolvePromise, reject) => {
+    const child = spawn(command, args, {
+      cwd: spawnOptions.cwd,
+      env: spawnOptions.env,
+      shell: spawnOptions.shell ?? false,
+      detached: true,
+      stdio: ["pipe", "pipe", "pipe"],
+    });
+
+    let output = "";
+    let stdout = "";
+    let stderr = "";
+    let timedOut = false;
+    let exitCode: number | null = null;
+    let exitSignal: NodeJS.Signals | null = null;
+    let killTimer: NodeJS.Timeout | undefined;
+
+    const pid = child.pid;
+    if (pid !== undefined) activeGroups.add(pid);
+
+    // SIGTERM the whole group, then SIGKILL whatever ignores it
+    const terminate = () => {
+      if (pid === undefined || killTimer) return;
+      killGroup(pid, "SIGTERM");
+      killTimer = setTimeout(() => killGroup(pid, "SIGKILL"), KILL_GRACE_MS);
+    };
+
+    const timer = setTimeout(() => {
+      timedOut = true;
+      console.log(`  Timed out after ${options.timeoutMs / 1000}s, stopping ${options.name ?? "agent"}`);
+      terminate();
+    }, options.timeoutMs);
+
+    const onData = (chunk: string) => {
+      output += chunk;
+      options.onOutput?.(chunk);
+    };
+    child.stdout.setEncoding("utf-8").on("data", (chunk: string) => {
+      stdout += chunk;
+      onData(chunk);
+    });
+    child.stderr.setEncoding("utf-8").on("data", (chunk: string) => {
+      stderr += chunk;
+      onData(chunk);
+    });
+    child.stdin.on("error", () => { /* exited without reading stdin */ });
+    child.stdin.end(spawnOptions.stdin);
+
+    child.on("error", (err) => {
+      clearTimeout(timer);
+      if (pid !== undefined) activeGroups.delete(pid);
+      reject(err);
+    });
+
+    // The process itself is done; stragglers in its group would otherwise hold
+    // the output pipes open (and keep running), so clean them up too
+    child.on("exit", (code, signal) => {
+      exitCode = code;
+      exitSignal = signal;
+      clearTimeout(timer);
+      terminate();
+    });
+
+    child.on("close", () => {
+      clearTimeout(killTimer);
+      if (pid !== undefined) activeGroups.delete(pid);
+      resolvePromise({ output, stdout, stderr, exitCode, signal: exitSignal, timedOut });
+    });
+  });
+}
+
+/** Kill every running process group when the suite is interrupted */
+export function installInterruptHandler(): void {
+  process.once("SIGINT", () => {
+    for (const pid of activeGroups) {
+      killGroup(pid, "SIGKILL");
+    }
+    process.exit(130);
+  });
+}
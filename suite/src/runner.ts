@@ -1,19 +1,38 @@
 #!/usr/bin/env node
-import { mkdirSync, writeFileSync, rmSync, readdirSync, existsSync, copyFileSync } from "node:fs";
-import { join, basename, dirname } from "node:path";
+import { mkdirSync, writeFileSync, appendFileSync, rmSync, readdirSync, existsSync, copyFileSync, renameSync, cpSync, statSync } from "node:fs";
+import { join, basename, dirname, resolve } from "node:path";
 import { homedir } from "node:os";
-import { execSync } from "node:child_process";
+import { execFileSync, execSync } from "node:child_process";
 import { parse, stringify } from "yaml";
 import { readFileSync } from "node:fs";
 import { createHash } from "node:crypto";
-import type { Scenario, TestResult, TestRun, Turn } from "./types.js";
-import { validateAll } from "./validator.js";
+import type { ErrorStatus, Scenario, TestResult, TestRun, Turn } from "./types.js";
+import { failsRun, flattenRules, runScore, scenarioRules, validateAll, validationLabel } from "./validator.js";
+import {
+  compareRuns,
+  findRun,
+  generateComparisonHtml,
+  generateHistoryHtml,
+  gitInfo,
+  loadHistory,
+  newRunId,
+  printComparison,
+  printHistory,
+  recordRun,
+} from "./history.js";
+import { DEFAULT_UPSTREAMS, startLlmProxy, type LlmProxy, type ProxyMode } from "./llm-proxy.js";
+import { startMockLlm, type MockLlm } from "./mock-llm.js";
+import type { JudgeConfig } from "./judge.js";
+import { DEFAULT_TIMEOUT_SECONDS, installInterruptHandler, runProcess, type ProcessOptions, type ProcessResult } from "./process.js";
+import { WORKSPACE_DIFF_HEADER, diffWorkspace, formatChanges, takeSnapshot, type FileChange, type WorkspaceSnapshot } from "./workspace.js";
 
 // =============================================================================
 // Types
 // =============================================================================
 
-type RunnerType = "goose" | "opencode" | "pi";
+type RunnerType = "goose" | "opencode" | "pi" | "command";
+
+const RUNNER_TYPES: RunnerType[] = ["goose", "opencode", "pi", "command"];
 
 interface ModelConfig {
   name: string;
@@ -27,6 +46,22 @@ interface RunnerConfig {
   bin: string;
   extensions?: string[];  // goose-specific
   stdio?: string[];       // MCP servers
+  command?: CommandTemplate;  // type: command
+  timeout?: number;           // seconds per agent invocation (overrides suite timeout)
+}
+
+// How to drive an arbitrary agent CLI (see "Command Runner" below)
+interface CommandTemplate {
+  args?: string[];
+  prompt?: "argv" | "stdin" | "file";  // default argv
+  session_args?: string[];     // multi-turn, first turn
+  resume_args?: string[];      // multi-turn, turn 2+
+  no_session_args?: string[];  // single-turn
+  env?: Record<string, string>;
+  mcp_config?: {
+    path: string;        // relative to the workdir; .yaml/.yml written as YAML, else JSON
+    template: unknown;   // rendered once per stdio server and deep-merged
+  };
 }
 
 interface MatrixEntry {
@@ -39,6 +74,16 @@ interface SuiteConfig {
   models: ModelConfig[];
   runners: RunnerConfig[];
   matrix?: MatrixEntry[];
+  parallel?: number;                     // max test pairs in flight (default 1)
+  concurrency?: Record<string, number>;  // per-provider cap, e.g. { ollama: 1 }
+  timeout?: number;                      // seconds per agent invocation (default 300)
+  pricing?: Record<string, ModelPrice>;  // model name -> USD per million tokens
+  retry?: {                              // for error:infra / error:provider runs
+    attempts?: number;                   // default 2
+    backoff?: number;                    // seconds before the first retry, doubled after (default 10)
+  };
+  upstreams?: Record<string, string>;    // provider -> API root the record proxy forwards to
+  judge?: JudgeConfig;                   // grader model for `judge` rules
 }
 
 // A test pair: scenario × model × runner
@@ -54,6 +99,52 @@ interface TestResultWithLog extends TestResult {
   toolCalls: number;
   turns: number;
   cached?: boolean;
+  attempts?: AttemptRecord[];  // every attempt of the pair, in order
+  stats?: AttemptStats;        // only with more than one attempt
+  usage?: TokenUsage;          // from the runner's session data, when it has any
+  cost?: number;               // USD, when the model has a price in config.yaml
+}
+
+interface TokenUsage {
+  input: number;
+  output: number;
+  cacheRead: number;
+  cacheWrite: number;
+}
+
+// USD per million tokens
+interface ModelPrice {
+  input: number;
+  output: number;
+  cache_read?: number;   // default: input
+  cache_write?: number;  // default: input
+}
+
+// How the attempts of a pair are reduced to the one result shown per cell
+type AggregatePolicy = "worst" | "best" | "majority";
+
+const AGGREGATE_POLICIES: AggregatePolicy[] = ["worst", "best", "majority"];
+
+interface AttemptRecord {
+  attempt: number;
+  status: TestRun["status"];
+  durationMs: number;
+  toolCalls: number;
+  logFile: string;
+  validations: Array<{ label: string; passed: boolean }>;
+  usage?: TokenUsage;
+  cost?: number;
+}
+
+interface AttemptStats {
+  n: number;        // attempts with a verdict (error:* attempts don't count)
+  k: number;
+  passes: number;
+  passAt1: number;  // chance a single attempt passes
+  passAtK: number;  // chance at least one of k attempts passes
+  passHatK: number; // chance all k attempts pass
+  validations: Array<{ label: string; passes: number }>;
+  flaky: boolean;   // passed some attempts but not all
 }
 
 // =============================================================================
@@ -73,11 +164,18 @@ interface CacheEntry {
   inputs: CacheInputs;
   result: {
     status: "passed" | "failed";
-    validations: Array<{ rule: any; passed: boolean; message?: string }>;
+    validations: Array<{ rule: any; passed: boolean; message?: string; score?: number }>;
     duration: number;
     toolCalls: number;
     turns: number;
     errors?: string[];
+    exitCode?: number | null;
+    signal?: string | null;
+    timedOut?: boolean;
+    attempts?: AttemptRecord[];
+    stats?: AttemptStats;
+    usage?: TokenUsage;
+    cost?: number;
   };
   logFile: string;
 }
@@ -148,14 +246,92 @@ function getMcpHarnessHash(): string {
   return "no-mcp-harness";
 }
 
-function computeCacheKey(pair: TestPair, binaryHashes: Map<string, string>, mcpHarnessHash: string): { key: string; inputs: CacheInputs } {
-  // Hash scenario content (name + prompt/turns + setup + validate)
+// Hash the modules behind `custom` rules (and schemas behind `data_schema`
+// rules) so editing a validator invalidates results
+function getCustomValidatorHashes(scenario: Scenario): Record<string, string> {
+  const rules = flattenRules(scenarioRules(scenario));
+  const hashes: Record<string, string> = {};
+  for (const rule of rules) {
+    if (rule.type !== "custom" && rule.type !== "data_schema") continue;
+    const file = rule.type === "custom" ? rule.fn : rule.schema;
+    const modulePath = join(scenario.dir ?? "", file.split("#")[0]);
+    try {
+      hashes[file] = sha256(readFileSync(modulePath));
+    } catch {
+      hashes[file] = "missing";
+    }
+  }
+  return hashes;
+}
+
+/** Content hash of every file below dir, paths included */
+function hashDir(dir: string): string {
+  const hash = createHash("sha256");
+  for (const entry of (readdirSync(dir, { recursive: true }) as string[]).sort()) {
+    const fullPath = join(dir, entry);
+    if (!statSync(fullPath).isFile()) continue;
+    hash.update(entry).update("\0").update(readFileSync(fullPath)).update("\0");
+  }
+  return hash.digest("hex").slice(0, 16);
+}
+
+const setupSourceHashes = new WeakMap<Scenario, Record<string, string> | undefined>();
+
+// Hash setup_dir / setup_archive / setup_git contents so fixture changes
+// invalidate results (once per scenario; every pair of it needs the same)
+function getSetupSourceHashes(scenario: Scenario): Record<string, string> | undefined {
+  if (setupSourceHashes.has(scenario)) return setupSourceHashes.get(scenario);
+  const { setup_dir, setup_archive, setup_git } = scenario;
+  let hashes: Record<string, string> | undefined;
+  if (setup_dir || setup_archive || setup_git) {
+    const source = (path: string) => resolve(scenario.dir ?? "", path);
+    hashes = {};
+    try {
+      if (setup_dir) hashes.dir = hashDir(source(setup_dir));
+      if (setup_archive) hashes.archive = sha256(readFileSync(source(setup_archive)));
+      if (setup_git) {
+        const repo = source(setup_git.repo);
+        // A repository pins the resolved commit; a bundle file is hashed whole
+        hashes.git = statSync(repo).isDirectory()
+          ? execFileSync("git", ["-C", repo, "rev-parse", "--verify", `${setup_git.ref ?? "HEAD"}^{commit}`], { encoding: "utf-8", stdio: "pipe" }).trim()
+          : sha256(readFileSync(repo));
+      }
+    } catch {
+      hashes.error = "unreadable";
+    }
+  }
+  setupSourceHashes.set(scenario, hashes);
+  return hashes;
+}
+
+function computeCacheKey(
+  pair: TestPair,
+  binaryHashes: Map<string, string>,
+  mcpHarnessHash: string,
+  aggregate: AggregatePolicy,
+  judge?: JudgeConfig
+): { key: string; inputs: CacheInputs } {
+  const rules = flattenRules(scenarioRules(pair.scenario));
+
+  // Hash scenario content (name + prompt/turns + setup + harness settings + validate + custom validator code)
   const scenarioContent = stringify({
     name: pair.scenario.name,
     prompt: pair.scenario.prompt,
     turns: pair.scenario.turns,
     setup: pair.scenario.setup,
+    fixtures: pair.scenario.fixtures,
+    faults: pair.scenario.faults,
+    now: pair.scenario.now,
+    seed: pair.scenario.seed,
     validate: pair.scenario.validate,
+    mock: pair.scenario.mock,
+    validators: getCustomValidatorHashes(pair.scenario),
+    setup_dir: pair.scenario.setup_dir,
+    setup_archive: pair.scenario.setup_archive,
+    setup_git: pair.scenario.setup_git,
+    setupSources: getSetupSourceHashes(pair.scenario),
+    // A different grader can give different verdicts
+    judge: rules.some((r) => r.type === "judge") ? judge?.model : undefined,
   });
   const scenarioHash = sha256(scenarioContent);
 
@@ -168,6 +344,7 @@ function computeCacheKey(pair: TestPair, binaryHashes: Map<string, string>, mcpH
     type: pair.runner.type,
     extensions: pair.runner.extensions ?? [],
     stdio: pair.runner.stdio ?? [],
+    command: pair.runner.command,
   });
   const runnerHash = sha256(runnerContent);
 
@@ -182,8 +359,9 @@ function computeCacheKey(pair: TestPair, binaryHashes: Map<string, string>, mcpH
     mcpHarnessHash,
   };
 
-  // Combine all into single key
-  const key = sha256(scenarioHash + modelKey + runnerHash + binaryHash + mcpHarnessHash);
+  // Combine all into single key. The aggregate policy is a run setting rather
+  // than a cell input, but a worst-of-N result can't stand in for best-of-N.
+  const key = sha256(scenarioHash + modelKey + runnerHash + binaryHash + mcpHarnessHash + aggregate);
 
   return { key, inputs };
 }
@@ -192,7 +370,8 @@ function getCachedResult(
   cache: CacheIndex,
   cacheKey: string,
   pair: TestPair,
-  logsDir: string
+  logsDir: string,
+  price?: ModelPrice
 ): TestResultWithLog | null {
   const entry = cache.entries[cacheKey];
   if (!entry) return null;
@@ -206,8 +385,7 @@ function getCachedResult(
   }
 
   // Copy cached log to current logs directory
-  const testId = `${pair.scenario.name}_${pair.model.name}_${pair.runner.name}`.replace(/[\/\\:]/g, "_");
-  const logFile = join(logsDir, `${testId}_cached.log`);
+  const logFile = join(logsDir, `${testIdFor(pair)}_cached.log`);
   mkdirSync(logsDir, { recursive: true });
   copyFileSync(cachedLogPath, logFile);
 
@@ -227,6 +405,9 @@ function getCachedResult(
     endTime: new Date(new Date(entry.timestamp).getTime() + entry.result.duration),
     status: entry.result.status,
     errors: entry.result.errors,
+    exitCode: entry.result.exitCode,
+    signal: entry.result.signal,
+    timedOut: entry.result.timedOut,
   };
 
   return {
@@ -237,6 +418,11 @@ function getCachedResult(
     toolCalls: entry.result.toolCalls,
     turns: entry.result.turns,
     cached: true,
+    // Prices aren't part of the key: cost comes from the cached usage at today's price
+    attempts: entry.result.attempts?.map((a) => ({ ...a, cost: a.usage && usageCost(a.usage, price) })),
+    stats: entry.result.stats,
+    usage: entry.result.usage,
+    cost: entry.result.usage && usageCost(entry.result.usage, price),
   };
 }
 
@@ -246,6 +432,12 @@ function storeCacheResult(
   inputs: CacheInputs,
   result: TestResultWithLog
 ): void {
+  // Errors aren't a verdict on the model; run them again next time. That
+  // includes a grader or rule that couldn't be evaluated, even one that
+  // doesn't decide the run
+  if (isErrorStatus(result.run.status)) return;
+  if (result.validations.some((v) => v.error)) return;
+
   // Copy log to cache directory
   const logFileName = `${cacheKey}.log`;
   const cachedLogPath = join(CACHE_LOGS_DIR, logFileName);
@@ -270,6 +462,13 @@ function storeCacheResult(
       toolCalls: result.toolCalls,
       turns: result.turns,
       errors: result.run.errors,
+      exitCode: result.run.exitCode,
+      signal: result.run.signal,
+      timedOut: result.run.timedOut,
+      attempts: result.attempts,
+      stats: result.stats,
+      usage: result.usage,
+      cost: result.cost,
     },
     logFile: logFileName,
   };
@@ -286,6 +485,79 @@ function clearCache(): void {
   }
 }
 
+// =============================================================================
+// MCP Harness Environment
+// =============================================================================
+
+// Per-scenario harness settings are written into the workdir: fixtures and fault
+// profiles as JSON files, plus the env vars pointing at them (and the clock/seed)
+const HARNESS_FIXTURES_FILE = ".harness-fixtures.json";
+const HARNESS_FAULTS_FILE = ".harness-faults.json";
+const HARNESS_ENV_FILE = ".harness-env.json";
+
+// Seed for harness IDs when a scenario doesn't set one
+const DEFAULT_HARNESS_SEED = 1;
+
+function writeHarnessFiles(scenario: Scenario, workdir: string): void {
+  const env: Record<string, string> = {
+    MCP_HARNESS_SEED: String(scenario.seed ?? DEFAULT_HARNESS_SEED),
+  };
+  if (scenario.now) {
+    env.MCP_HARNESS_NOW = new Date(scenario.now).toISOString();
+  }
+  if (scenario.fixtures) {
+    env.MCP_HARNESS_FIXTURES = join(workdir, HARNESS_FIXTURES_FILE);
+    writeFileSync(env.MCP_HARNESS_FIXTURES, JSON.stringify(scenario.fixtures, null, 2));
+  }
+  if (scenario.faults) {
+    env.MCP_HARNESS_FAULTS = join(workdir, HARNESS_FAULTS_FILE);
+    writeFileSync(env.MCP_HARNESS_FAULTS, JSON.stringify(scenario.faults, null, 2));
+  }
+  writeFileSync(join(workdir, HARNESS_ENV_FILE), JSON.stringify(env, null, 2));
+}
+
+/** Env vars for MCP harness processes started by an agent in this workdir */
+function harnessEnv(workdir: string): Record<string, string> {
+  const envPath = join(workdir, HARNESS_ENV_FILE);
+  return {
+    MCP_HARNESS_LOG: join(workdir, "tool-calls.log"),
+    ...(existsSync(envPath) ? JSON.parse(readFileSync(envPath, "utf-8")) : {}),
+  };
+}
+
+// =============================================================================
+// LLM Proxy
+// =============================================================================
+
+// With --record/--replay every agent's model traffic goes through a local
+// proxy (llm-proxy.ts), addressed per test pair so recordings don't mix
+let llmProxy: LlmProxy | undefined;
+
+const RECORDINGS_DIR = join(import.meta.dirname, "../.recordings");
+
+// Serves `provider: mock` models from the scenario's scripts (mock-llm.ts);
+// started when the run includes a mock model
+let mockLlm: MockLlm | undefined;
+
+// Providers the agents don't know natively: set up as OpenAI-compatible
+// custom providers pointed at providerRoot()
+const CUSTOM_PROVIDERS = new Set(["ollama", "mock"]);
+
+/**
+ * API root (without /v1) an agent in this workdir should use for a provider:
+ * the mock server for mock models, the proxy when recording/replaying, else
+ * ollama's local server, else undefined (the agent's built-in default).
+ */
+function providerRoot(provider: string, workdir: string): string | undefined {
+  if (provider === "mock") {
+    return mockLlm?.baseFor(basename(workdir));
+  }
+  if (llmProxy) {
+    return llmProxy.baseFor(basename(workdir), provider);
+  }
+  return provider === "ollama" ? DEFAULT_UPSTREAMS.ollama : undefined;
+}
+
 // =============================================================================
 // Goose Runner
 // =============================================================================
@@ -295,9 +567,14 @@ const PLATFORM_EXTENSIONS = new Set([
   "chatrecall", "apps", "imagegenerator"
 ]);
 
-// Isolated goose config directory
+// Isolated goose config directory (one subdirectory per test pair so
+// concurrent runs don't overwrite each other's config.yaml)
 const GOOSE_ROOT = join(import.meta.dirname, "../.goose-root");
-const GOOSE_CONFIG_DIR = join(GOOSE_ROOT, "config");
+
+// Goose has no mock provider; its OpenAI provider pointed at the mock server does the job
+function gooseProvider(model: ModelConfig): string {
+  return model.provider === "mock" ? "openai" : model.provider;
+}
 
 function generateGooseConfig(model: ModelConfig, runner: RunnerConfig): object {
   const extensions: Record<string, object> = {};
@@ -341,7 +618,7 @@ function generateGooseConfig(model: ModelConfig, runner: RunnerConfig): object {
 
   return {
     extensions,
-    GOOSE_PROVIDER: model.provider,
+    GOOSE_PROVIDER: gooseProvider(model),
     GOOSE_MODEL: model.model,
     GOOSE_TELEMETRY_ENABLED: false,
   };
@@ -352,16 +629,24 @@ async function runGooseAgent(
   runner: RunnerConfig,
   prompt: string,
   workdir: string,
+  proc: ProcessOptions,
   sessionName?: string,  // If provided, use/continue this session
   resume: boolean = false  // If true, resume existing session (for turn 2+)
-): Promise<string> {
+): Promise<ProcessResult> {
   const promptFile = join(workdir, ".goose-prompt.txt");
   writeFileSync(promptFile, prompt);
 
   // Write goose config
-  mkdirSync(GOOSE_CONFIG_DIR, { recursive: true });
+  const gooseRoot = join(GOOSE_ROOT, basename(workdir));
+  const gooseConfigDir = join(gooseRoot, "config");
+  mkdirSync(gooseConfigDir, { recursive: true });
   const gooseConfig = generateGooseConfig(model, runner);
-  writeFileSync(join(GOOSE_CONFIG_DIR, "config.yaml"), stringify(gooseConfig));
+  writeFileSync(join(gooseConfigDir, "config.yaml"), stringify(gooseConfig));
+
+  // Start each run with no sessions, so the ones left afterwards are this run's (token usage)
+  if (!resume) {
+    rmSync(join(gooseRoot, "data"), { recursive: true, force: true });
+  }
 
   let cmd: string;
   if (sessionName) {
@@ -374,29 +659,33 @@ async function runGooseAgent(
       console.log(`  Running: ${runner.bin} run -i <prompt> --name "${sessionName}"`);
     }
   } else {
-    cmd = `${runner.bin} run -i "${promptFile}" --no-session`;
-    console.log(`  Running: ${runner.bin} run -i <prompt> --no-session`);
+    // Single-turn: still a named session (in the isolated root) so token usage is recorded
+    const runSession = `run_${basename(workdir)}`;
+    cmd = `${runner.bin} run -i "${promptFile}" --name "${runSession}"`;
+    console.log(`  Running: ${runner.bin} run -i <prompt> --name "${runSession}"`);
   }
 
-  const output = execSync(cmd, {
+  return runProcess(cmd, [], {
     cwd: workdir,
     env: {
       ...process.env,
-      GOOSE_PATH_ROOT: GOOSE_ROOT,
-      MCP_HARNESS_LOG: join(workdir, "tool-calls.log"),
+      GOOSE_PATH_ROOT: gooseRoot,
+      // Goose reads <PROVIDER>_HOST (OLLAMA_HOST, ANTHROPIC_HOST, OPENAI_HOST, ...)
+      ...(llmProxy || model.provider === "mock"
+        ? { [`${gooseProvider(model).toUpperCase()}_HOST`]: providerRoot(model.provider, workdir) }
+        : {}),
+      ...(model.provider === "mock" ? { OPENAI_API_KEY: "mock" } : {}),
+      ...harnessEnv(workdir),
     },
-    timeout: 5 * 60 * 1000,
-    encoding: "utf-8",
-  });
-
-  return output;
+    shell: "/bin/sh",
+  }, proc);
 }
 
 // =============================================================================
 // OpenCode Runner
 // =============================================================================
 
-// Isolated opencode config directory
+// Isolated opencode config directory (one subdirectory per test pair)
 const OPENCODE_ROOT = join(import.meta.dirname, "../.opencode-root");
 
 function generateOpenCodeConfig(model: ModelConfig, runner: RunnerConfig, workdir: string): object {
@@ -413,9 +702,7 @@ function generateOpenCodeConfig(model: ModelConfig, runner: RunnerConfig, workdi
       type: "local",
       command: [cmd, ...args],
       enabled: true,
-      environment: {
-        MCP_HARNESS_LOG: join(workdir, "tool-calls.log"),
-      },
+      environment: harnessEnv(workdir),
     };
   }
 
@@ -424,15 +711,15 @@ function generateOpenCodeConfig(model: ModelConfig, runner: RunnerConfig, workdi
     mcp,
   };
 
-  // Handle ollama as a custom provider (OpenCode doesn't have built-in ollama support)
-  if (model.provider === "ollama") {
-    config.model = `ollama/${model.model}`;
+  // Handle ollama and mock as custom providers (OpenCode doesn't have them built in)
+  if (CUSTOM_PROVIDERS.has(model.provider)) {
+    config.model = `${model.provider}/${model.model}`;
     config.provider = {
-      ollama: {
+      [model.provider]: {
         npm: "@ai-sdk/openai-compatible",
-        name: "Ollama (local)",
+        name: model.provider === "mock" ? "Mock (scripted)" : "Ollama (local)",
         options: {
-          baseURL: "http://localhost:11434/v1",
+          baseURL: `${providerRoot(model.provider, workdir)}/v1`,
         },
         models: {
           [model.model]: {
@@ -444,6 +731,13 @@ function generateOpenCodeConfig(model: ModelConfig, runner: RunnerConfig, workdi
   } else {
     // Standard providers (anthropic, openai, etc.)
     config.model = `${model.provider}/${model.model}`;
+    if (llmProxy) {
+      config.provider = {
+        [model.provider]: {
+          options: { baseURL: `${providerRoot(model.provider, workdir)}/v1` },
+        },
+      };
+    }
   }
 
   return config;
@@ -454,8 +748,9 @@ async function runOpenCodeAgent(
   runner: RunnerConfig,
   prompt: string,
   workdir: string,
+  proc: ProcessOptions,
   resume: boolean = false
-): Promise<string> {
+): Promise<ProcessResult> {
   // Write opencode.json config to workdir
   const openCodeConfig = generateOpenCodeConfig(model, runner, workdir);
   writeFileSync(join(workdir, "opencode.json"), JSON.stringify(openCodeConfig, null, 2));
@@ -465,26 +760,28 @@ async function runOpenCodeAgent(
   writeFileSync(promptFile, prompt);
 
   // Ensure isolated config directory exists
-  mkdirSync(OPENCODE_ROOT, { recursive: true });
+  const openCodeRoot = join(OPENCODE_ROOT, basename(workdir));
+  mkdirSync(openCodeRoot, { recursive: true });
+
+  // Start each run with empty session storage, so its messages are this run's (token usage)
+  if (!resume) {
+    rmSync(join(openCodeRoot, "opencode", "storage"), { recursive: true, force: true });
+  }
 
   // Use --continue on turn 2+ to continue last session
   const continueFlag = resume ? "--continue " : "";
   const cmd = `${runner.bin} run ${continueFlag}"$(cat "${promptFile}")"`;
   console.log(`  Running: ${runner.bin} run ${continueFlag}"<prompt>"`);
 
-  const output = execSync(cmd, {
+  return runProcess(cmd, [], {
     cwd: workdir,
     env: {
       ...process.env,
-      XDG_CONFIG_HOME: OPENCODE_ROOT,
-      XDG_DATA_HOME: OPENCODE_ROOT,
+      XDG_CONFIG_HOME: openCodeRoot,
+      XDG_DATA_HOME: openCodeRoot,
     },
-    timeout: 5 * 60 * 1000,
-    encoding: "utf-8",
     shell: "/bin/bash",
-  });
-
-  return output;
+  }, proc);
 }
 
 
@@ -495,28 +792,40 @@ async function runOpenCodeAgent(
 // Pi takes --provider and --model as CLI arguments
 // MCP support via pi-mcp-adapter: `pi install npm:pi-mcp-adapter`
 
-// Isolated Pi config directory (like Goose/OpenCode)
+// Isolated Pi config directory (like Goose/OpenCode, one subdirectory per test pair)
 const PI_CONFIG_DIR = join(import.meta.dirname, "../.pi-root");
 
+// Session file for single-turn runs (multi-turn use .pi-session-<name>.jsonl)
+const PI_RUN_SESSION_FILE = ".pi-session.jsonl";
+
 // User's real Pi config (for copying auth.json)
 const PI_USER_CONFIG = join(homedir(), ".pi", "agent");
 
 /**
  * Generate models.json for Pi with the test model.
- * For ollama models, we need to define them since Pi doesn't have built-in ollama support.
+ * For ollama and mock models, we need to define them since Pi doesn't have them built in.
+ * Built-in providers only get a baseUrl override, and only when proxied.
  */
-function generatePiModelsConfig(model: ModelConfig): object {
-  // Only generate config for ollama provider (others are built-in)
-  if (model.provider !== "ollama") {
-    return { providers: {} };
+function generatePiModelsConfig(model: ModelConfig, workdir: string): object {
+  if (!CUSTOM_PROVIDERS.has(model.provider)) {
+    if (!llmProxy) {
+      return { providers: {} };
+    }
+    // The Anthropic SDK appends /v1 itself; OpenAI-style clients expect it in the base
+    const root = providerRoot(model.provider, workdir);
+    return {
+      providers: {
+        [model.provider]: { baseUrl: model.provider === "anthropic" ? root : `${root}/v1` },
+      },
+    };
   }
 
   return {
     providers: {
-      ollama: {
-        baseUrl: "http://localhost:11434/v1",
+      [model.provider]: {
+        baseUrl: `${providerRoot(model.provider, workdir)}/v1`,
         api: "openai-completions",
-        apiKey: "ollama",  // Ollama doesn't need a real key
+        apiKey: model.provider,  // Neither needs a real key
         models: [
           {
             id: model.model,
@@ -542,30 +851,32 @@ async function runPiAgent(
   runner: RunnerConfig,
   prompt: string,
   workdir: string,
+  proc: ProcessOptions,
   sessionName?: string,  // If provided, use/continue this session (for multi-turn)
   resume: boolean = false  // If true, continue existing session (for turn 2+)
-): Promise<string> {
+): Promise<ProcessResult> {
   // Write prompt to file (use cat to avoid shell escaping issues)
   const promptFile = join(workdir, ".pi-prompt.txt");
   writeFileSync(promptFile, prompt);
 
   // Set up isolated Pi config directory
-  mkdirSync(PI_CONFIG_DIR, { recursive: true });
+  const agentDir = join(PI_CONFIG_DIR, basename(workdir));
+  mkdirSync(agentDir, { recursive: true });
 
-  // Generate models.json with the test model (for ollama)
-  const modelsConfig = generatePiModelsConfig(model);
-  writeFileSync(join(PI_CONFIG_DIR, "models.json"), JSON.stringify(modelsConfig, null, 2));
+  // Generate models.json with the test model (for ollama/mock)
+  const modelsConfig = generatePiModelsConfig(model, workdir);
+  writeFileSync(join(agentDir, "models.json"), JSON.stringify(modelsConfig, null, 2));
 
   // Copy auth.json from user's config (for API keys)
   const userAuthPath = join(PI_USER_CONFIG, "auth.json");
   if (existsSync(userAuthPath)) {
-    copyFileSync(userAuthPath, join(PI_CONFIG_DIR, "auth.json"));
+    copyFileSync(userAuthPath, join(agentDir, "auth.json"));
   }
 
   // Copy settings.json from user's config (for installed packages like pi-mcp-adapter)
   const userSettingsPath = join(PI_USER_CONFIG, "settings.json");
   if (existsSync(userSettingsPath)) {
-    copyFileSync(userSettingsPath, join(PI_CONFIG_DIR, "settings.json"));
+    copyFileSync(userSettingsPath, join(agentDir, "settings.json"));
   }
 
   // If runner has stdio MCP servers, write .pi/mcp.json to the workdir (project config)
@@ -597,9 +908,7 @@ async function runPiAgent(
         command: parts[0],
         args: parts.slice(1),
         lifecycle: "eager",  // Connect at startup for tests
-        env: {
-          MCP_HARNESS_LOG: join(workdir, "tool-calls.log")
-        }
+        env: harnessEnv(workdir)
       };
     });
 
@@ -625,39 +934,146 @@ async function runPiAgent(
       cmd += ` --session "${sessionPath}"`;
     }
   } else {
-    // Single-turn: don't save session
-    cmd += ` --no-session`;
+    // Single-turn: a throwaway session in the workdir, kept only for token usage
+    cmd += ` --session "${join(workdir, PI_RUN_SESSION_FILE)}"`;
   }
 
   cmd += ` "$(cat "${promptFile}")"`;
 
   // Build log message
-  const sessionInfo = sessionName 
-    ? (resume ? ` --continue --session <session>` : ` --session <session>`)
-    : ` --no-session`;
+  const sessionInfo = sessionName && resume ? ` --continue --session <session>` : ` --session <session>`;
   console.log(`  Running: ${runner.bin} -p${sessionInfo} --provider ${model.provider} --model "${model.model}"${hasMcp ? ' (mcp)' : ''} "<prompt>"`);
 
-  const output = execSync(cmd, {
+  return runProcess(cmd, [], {
     cwd: workdir,
     env: {
       ...process.env,
-      PI_CODING_AGENT_DIR: PI_CONFIG_DIR,  // Use isolated config dir
-      MCP_HARNESS_LOG: join(workdir, "tool-calls.log"),
+      PI_CODING_AGENT_DIR: agentDir,  // Use isolated config dir
+      ...harnessEnv(workdir),
     },
-    timeout: 5 * 60 * 1000,
-    encoding: "utf-8",
     shell: "/bin/bash",
-  });
+  }, proc);
+}
+
+// =============================================================================
+// Command Runner
+// =============================================================================
+
+// Drives any agent CLI from the `command` template in config.yaml. Placeholders:
+//   args/env:    {{model}} {{provider}} {{model_name}} {{workdir}} {{session}}
+//                {{prompt}} {{prompt_file}} {{llm_base_url}}
+//   mcp_config:  all of the above plus {{name}} {{cmd}} {{args}} {{log}} {{env}}
+// A string that is exactly "{{args}}" or "{{env}}" becomes the array/object itself.
+
+function renderTemplate(value: unknown, vars: Record<string, unknown>): unknown {
+  if (typeof value === "string") {
+    const whole = /^\{\{(\w+)\}\}$/.exec(value);
+    if (whole && whole[1] in vars) return vars[whole[1]];
+    return value.replace(/\{\{(\w+)\}\}/g, (match, key) => (key in vars ? String(vars[key]) : match));
+  }
+  if (Array.isArray(value)) {
+    return value.map((v) => renderTemplate(v, vars));
+  }
+  if (value && typeof value === "object") {
+    return Object.fromEntries(
+      Object.entries(value).map(([k, v]) => [String(renderTemplate(k, vars)), renderTemplate(v, vars)])
+    );
+  }
+  return value;
+}
+
+function deepMerge(a: any, b: any): any {
+  const isObject = (v: any) => v && typeof v === "object" && !Array.isArray(v);
+  if (!isObject(a) || !isObject(b)) return b;
+  const merged = { ...a };
+  for (const [key, value] of Object.entries(b)) {
+    merged[key] = key in merged ? deepMerge(merged[key], value) : value;
+  }
+  return merged;
+}
+
+async function runCommandAgent(
+  model: ModelConfig,
+  runner: RunnerConfig,
+  prompt: string,
+  workdir: string,
+  proc: ProcessOptions,
+  sessionName?: string,  // If provided, use/continue this session (for multi-turn)
+  resume: boolean = false  // If true, continue existing session (for turn 2+)
+): Promise<ProcessResult> {
+  const template: CommandTemplate = runner.command ?? {};
+  const promptMode = template.prompt ?? "argv";
+
+  const promptFile = join(workdir, ".command-prompt.txt");
+  writeFileSync(promptFile, prompt);
+
+  const vars: Record<string, unknown> = {
+    model: model.model,
+    provider: model.provider,
+    model_name: model.name,
+    workdir,
+    session: sessionName ?? "",
+    prompt,
+    prompt_file: promptFile,
+    llm_base_url: providerRoot(model.provider, workdir) ?? "",  // API root, no /v1
+  };
+
+  // Write the agent's MCP config, one template render per stdio server
+  if (template.mcp_config && runner.stdio?.length) {
+    let mcpConfig: unknown = {};
+    for (const extCmd of runner.stdio) {
+      const parts = extCmd.split(" ");
+      const cmd = parts[0];
+      const args = parts.slice(1);
+      const name = basename(args[args.length - 1] || cmd).replace(/\.[^.]+$/, "");
+      mcpConfig = deepMerge(mcpConfig, renderTemplate(template.mcp_config.template, {
+        ...vars,
+        name,
+        cmd,
+        args,
+        log: join(workdir, "tool-calls.log"),
+        env: harnessEnv(workdir),
+      }));
+    }
+    const configPath = resolve(workdir, String(renderTemplate(template.mcp_config.path, vars)));
+    mkdirSync(dirname(configPath), { recursive: true });
+    writeFileSync(configPath, /\.ya?ml$/.test(configPath) ? stringify(mcpConfig) : JSON.stringify(mcpConfig, null, 2));
+  }
+
+  // Session handling for multi-turn
+  const sessionArgs = sessionName
+    ? (resume ? template.resume_args : template.session_args)
+    : template.no_session_args;
+  const argTemplates = [...(template.args ?? []), ...(sessionArgs ?? [])];
+  const args = argTemplates.map((arg) => String(renderTemplate(arg, vars)));
+
+  // Pass the prompt as the last argument unless the template already places it
+  const mentions = (placeholder: string) => argTemplates.some((arg) => arg.includes(placeholder));
+  if (promptMode === "argv" && !mentions("{{prompt}}")) {
+    args.push(prompt);
+  } else if (promptMode === "file" && !mentions("{{prompt_file}}")) {
+    args.push(promptFile);
+  }
+
+  const shownArgs = args.map((arg) => (arg === prompt ? "<prompt>" : arg.includes(prompt) ? "<...prompt...>" : arg));
+  console.log(`  Running: ${runner.bin} ${shownArgs.join(" ")}${promptMode === "stdin" ? " < <prompt>" : ""}`);
 
-  return output;
+  return runProcess(runner.bin, args, {
+    cwd: workdir,
+    env: {
+      ...process.env,
+      ...harnessEnv(workdir),
+      ...(renderTemplate(template.env ?? {}, vars) as Record<string, string>),
+    },
+    stdin: promptMode === "stdin" ? prompt : undefined,
+  }, proc);
 }
 
 // =============================================================================
 // Unified Runner
 // =============================================================================
 
-interface AgentResult {
-  output: string;
+interface AgentResult extends ProcessResult {
   sessionId?: string;  // For multi-turn (goose, pi)
 }
 
@@ -666,19 +1082,148 @@ async function runAgent(
   runner: RunnerConfig,
   prompt: string,
   workdir: string,
-  sessionId?: string,  // For multi-turn (goose, pi)
+  proc: ProcessOptions,
+  sessionId?: string,  // For multi-turn (goose, pi, command)
   resume: boolean = false  // For multi-turn: true on turn 2+
 ): Promise<AgentResult> {
-  if (runner.type === "opencode") {
-    const output = await runOpenCodeAgent(model, runner, prompt, workdir, resume);
-    return { output };
+  switch (runner.type) {
+    case "opencode":
+      return runOpenCodeAgent(model, runner, prompt, workdir, proc, resume);
+    case "pi": {
+      const result = await runPiAgent(model, runner, prompt, workdir, proc, sessionId, resume);
+      return { ...result, sessionId };
+    }
+    case "command": {
+      const result = await runCommandAgent(model, runner, prompt, workdir, proc, sessionId, resume);
+      return { ...result, sessionId };
+    }
+    case "goose": {
+      const result = await runGooseAgent(model, runner, prompt, workdir, proc, sessionId, resume);
+      return { ...result, sessionId };
+    }
+    default:
+      throw new Error(`Unknown runner type "${runner.type}" for runner "${runner.name}"`);
   }
-  if (runner.type === "pi") {
-    const output = await runPiAgent(model, runner, prompt, workdir, sessionId, resume);
-    return { output, sessionId };
+}
+
+// =============================================================================
+// Token Usage
+// =============================================================================
+
+// Read back what each agent recorded about its own token usage. Runners clear
+// their session storage at the start of a run, so everything found is this run's.
+
+function emptyUsage(): TokenUsage {
+  return { input: 0, output: 0, cacheRead: 0, cacheWrite: 0 };
+}
+
+function listFiles(dir: string, extension: string): string[] {
+  if (!existsSync(dir)) return [];
+  return (readdirSync(dir, { recursive: true }) as string[])
+    .filter((f) => f.endsWith(extension))
+    .map((f) => join(dir, f));
+}
+
+function readGooseUsage(gooseRoot: string): TokenUsage | undefined {
+  const sessionsDir = join(gooseRoot, "data", "sessions");
+  const usage = emptyUsage();
+  let found = false;
+
+  // Older goose: one JSONL file per session, metadata on the first line
+  for (const file of listFiles(sessionsDir, ".jsonl")) {
+    try {
+      const meta = JSON.parse(readFileSync(file, "utf-8").split("\n")[0]);
+      usage.input += meta.accumulated_input_tokens ?? meta.input_tokens ?? 0;
+      usage.output += meta.accumulated_output_tokens ?? meta.output_tokens ?? 0;
+      found = true;
+    } catch (e) { /* not a session file */ }
   }
-  const output = await runGooseAgent(model, runner, prompt, workdir, sessionId, resume);
-  return { output, sessionId };
+
+  // Newer goose: SQLite session database (read with the sqlite3 CLI, if installed)
+  const sessionsDb = join(sessionsDir, "sessions.db");
+  if (existsSync(sessionsDb)) {
+    try {
+      const row = execFileSync("sqlite3", [
+        "-readonly",
+        sessionsDb,
+        "SELECT COALESCE(SUM(accumulated_input_tokens), 0), COALESCE(SUM(accumulated_output_tokens), 0) FROM sessions",
+      ], { encoding: "utf-8", stdio: "pipe" });
+      const [input, output] = row.trim().split("|").map(Number);
+      usage.input += input || 0;
+      usage.output += output || 0;
+      found = true;
+    } catch (e) { /* no sqlite3, or a schema we don't know */ }
+  }
+
+  return found ? usage : undefined;
+}
+
+function readOpenCodeUsage(openCodeRoot: string): TokenUsage | undefined {
+  // One JSON file per message under storage/message/<session>/
+  const files = listFiles(join(openCodeRoot, "opencode", "storage", "message"), ".json");
+  const usage = emptyUsage();
+  let found = false;
+  for (const file of files) {
+    try {
+      const message = JSON.parse(readFileSync(file, "utf-8"));
+      if (message.role !== "assistant" || !message.tokens) continue;
+      usage.input += message.tokens.input ?? 0;
+      usage.output += (message.tokens.output ?? 0) + (message.tokens.reasoning ?? 0);
+      usage.cacheRead += message.tokens.cache?.read ?? 0;
+      usage.cacheWrite += message.tokens.cache?.write ?? 0;
+      found = true;
+    } catch (e) { /* partially written or not a message */ }
+  }
+  return found ? usage : undefined;
+}
+
+function readPiUsage(workdir: string): TokenUsage | undefined {
+  const files = readdirSync(workdir).filter((f) => f.startsWith(".pi-session") && f.endsWith(".jsonl"));
+  const usage = emptyUsage();
+  let found = false;
+  for (const file of files) {
+    for (const line of readFileSync(join(workdir, file), "utf-8").split("\n")) {
+      if (!line.trim()) continue;
+      try {
+        const entry = JSON.parse(line);
+        const u = entry.message?.usage ?? entry.usage;
+        if (!u) continue;
+        usage.input += u.input ?? 0;
+        usage.output += u.output ?? 0;
+        usage.cacheRead += u.cacheRead ?? 0;
+        usage.cacheWrite += u.cacheWrite ?? 0;
+        found = true;
+      } catch (e) { /* skip malformed lines */ }
+    }
+  }
+  return found ? usage : undefined;
+}
+
+function collectUsage(runner: RunnerConfig, workdir: string): TokenUsage | undefined {
+  try {
+    switch (runner.type) {
+      case "goose":
+        return readGooseUsage(join(GOOSE_ROOT, basename(workdir)));
+      case "opencode":
+        return readOpenCodeUsage(join(OPENCODE_ROOT, basename(workdir)));
+      case "pi":
+        return readPiUsage(workdir);
+      default:
+        return undefined;  // command runners: no known session format
+    }
+  } catch (e) {
+    return undefined;
+  }
+}
+
+function usageCost(usage: TokenUsage, price?: ModelPrice): number | undefined {
+  if (!price) return undefined;
+  return (
+    usage.input * price.input +
+    usage.output * price.output +
+    usage.cacheRead * (price.cache_read ?? price.input) +
+    usage.cacheWrite * (price.cache_write ?? price.input)
+  ) / 1_000_000;
 }
 
 // =============================================================================
@@ -687,7 +1232,40 @@ async function runAgent(
 
 function loadScenario(path: string): Scenario {
   const content = readFileSync(path, "utf-8");
-  return parse(content) as Scenario;
+  const scenario = parse(content) as Scenario;
+  scenario.dir = dirname(path);
+
+  // The harness gets now: as an ISO string; a typo would throw mid-run
+  if (scenario.now !== undefined && Number.isNaN(new Date(scenario.now).getTime())) {
+    throw new Error(`Scenario "${scenario.name}": now is not a valid ISO 8601 time: ${JSON.stringify(scenario.now)}`);
+  }
+
+  // expect: describes the scripted run; without a script it could never hold
+  if (scenario.expect !== undefined) {
+    if (!hasMockScript(scenario)) {
+      throw new Error(`Scenario "${scenario.name}": expect needs a mock script`);
+    }
+    const { status, failing } = scenario.expect;
+    if (status !== undefined && status !== "passed" && status !== "failed") {
+      throw new Error(`Scenario "${scenario.name}": expect.status must be passed or failed, got ${JSON.stringify(status)}`);
+    }
+    if (failing !== undefined && !(Array.isArray(failing) && failing.every((l) => typeof l === "string"))) {
+      throw new Error(`Scenario "${scenario.name}": expect.failing must be a list of validation names`);
+    }
+  }
+
+  // A missing fixture fails here rather than halfway through a run
+  const sources: Array<[string, string | undefined]> = [
+    ["setup_dir", scenario.setup_dir],
+    ["setup_archive", scenario.setup_archive],
+    ["setup_git.repo", scenario.setup_git?.repo],
+  ];
+  for (const [field, source] of sources) {
+    if (source && !existsSync(resolve(scenario.dir, source))) {
+      throw new Error(`Scenario "${scenario.name}": ${field} not found: ${source}`);
+    }
+  }
+  return scenario;
 }
 
 function loadAllScenarios(dir: string): Scenario[] {
@@ -695,11 +1273,33 @@ function loadAllScenarios(dir: string): Scenario[] {
   return files.map((f) => loadScenario(join(dir, f)));
 }
 
+/** An integer >= 1 (a worker count or limit); anything else would stall the pool */
+function positiveInt(value: unknown, source: string): number {
+  const n = typeof value === "string" ? Number(value.trim() || NaN) : value;
+  if (typeof n !== "number" || !Number.isInteger(n) || n < 1) {
+    throw new Error(`${source} must be an integer >= 1, got ${JSON.stringify(value)}`);
+  }
+  return n;
+}
+
 function loadConfig(configPath: string): SuiteConfig {
   const content = readFileSync(configPath, "utf-8");
   const config = parse(content) as SuiteConfig;
   const configDir = join(configPath, "..");
 
+  for (const runner of config.runners) {
+    if (!RUNNER_TYPES.includes(runner.type)) {
+      throw new Error(`Unknown runner type "${runner.type}" for runner "${runner.name}". Supported: ${RUNNER_TYPES.join(", ")}`);
+    }
+    if (runner.type === "command" && !runner.command) {
+      throw new Error(`Runner "${runner.name}" has type: command but no command template`);
+    }
+  }
+
+  for (const [provider, limit] of Object.entries(config.concurrency ?? {})) {
+    positiveInt(limit, `concurrency.${provider} in config.yaml`);
+  }
+
   // Resolve relative paths in stdio for all runners
   for (const runner of config.runners) {
     if (runner.stdio) {
@@ -723,6 +1323,40 @@ function loadConfig(configPath: string): SuiteConfig {
 function setupWorkdir(scenario: Scenario, workdir: string): void {
   rmSync(workdir, { recursive: true, force: true });
   mkdirSync(workdir, { recursive: true });
+  const source = (path: string) => resolve(scenario.dir ?? "", path);
+
+  // Git first (clone wants an empty directory), then the archive and the
+  // directory on top, then inline files over everything
+  if (scenario.setup_git) {
+    const { repo, ref } = scenario.setup_git;
+    execFileSync("git", ["clone", "--quiet", source(repo), workdir], { stdio: "pipe" });
+    if (ref) {
+      // In the clone a branch of the source only exists as origin/<ref>, so
+      // resolve the ref to a commit first: in the source repository, or for
+      // a bundle in the clone (where its branches are origin/<ref> too)
+      const revParse = (dir: string, rev: string) =>
+        execFileSync("git", ["-C", dir, "rev-parse", "--verify", `${rev}^{commit}`], { encoding: "utf-8", stdio: "pipe" }).trim();
+      let sha: string;
+      if (statSync(source(repo)).isDirectory()) {
+        sha = revParse(source(repo), ref);
+      } else {
+        try {
+          sha = revParse(workdir, `origin/${ref}`);
+        } catch {
+          sha = revParse(workdir, ref);
+        }
+      }
+      execFileSync("git", ["-C", workdir, "checkout", "--quiet", "--detach", sha], { stdio: "pipe" });
+    }
+    // Keep the agent from fetching from or pushing to the fixture repository
+    execFileSync("git", ["-C", workdir, "remote", "remove", "origin"], { stdio: "pipe" });
+  }
+  if (scenario.setup_archive) {
+    execFileSync("tar", ["-xf", source(scenario.setup_archive), "-C", workdir], { stdio: "pipe" });
+  }
+  if (scenario.setup_dir) {
+    cpSync(source(scenario.setup_dir), workdir, { recursive: true });
+  }
 
   if (scenario.setup) {
     for (const [path, content] of Object.entries(scenario.setup)) {
@@ -765,10 +1399,84 @@ function parseLogMetrics(logContent: string, workdir?: string): { toolCalls: num
   return { toolCalls, turns };
 }
 
+// =============================================================================
+// Failure Classification
+// =============================================================================
+
+// Failures are classified only from what the runner itself observed: the
+// spawn error, exit code/signal, the proxy's upstream status and the agent
+// CLI's stderr. Stdout carries the transcript (tool results, model text), which
+// in scenarios like flaky-app-automation is full of injected "rate limit" and
+// 503 errors that say nothing about the provider.
+const INFRA_PATTERNS = [
+  /ECONNREFUSED|connection refused/i,
+  /ENOTFOUND|getaddrinfo|error sending request|failed to connect|could not connect/i,
+];
+
+const PROVIDER_PATTERNS = [
+  /overloaded/i,
+  /rate[ _-]?limit|too many requests/i,
+  /\b(?:status|HTTP|code)[ :=]*(?:429|5\d\d)\b/i,
+  /internal server error|service unavailable|bad gateway|gateway timeout/i,
+  /insufficient[_ ]quota|credit balance/i,
+];
+
+const FAILURE_TAIL_CHARS = 4000;
+
+const RETRYABLE_STATUSES = new Set<TestRun["status"]>(["error:infra", "error:provider"]);
+
+function isErrorStatus(status: TestRun["status"]): status is ErrorStatus {
+  return status.startsWith("error:");
+}
+
+/**
+ * Decide whether a run that threw is the model's failure or the environment's.
+ * upstreamStatus is the proxy's last model response status when recording or
+ * replaying (0 = none arrived), which then overrides the stderr heuristics.
+ */
+function classifyFailure(
+  err: unknown,
+  run: TestRun,
+  stderr: string,
+  upstreamStatus: number | undefined
+): TestRun["status"] {
+  if (run.timedOut) return "error:timeout";
+
+  // Binary missing or not executable (direct spawn, or via the shell)
+  const code = (err as NodeJS.ErrnoException)?.code;
+  if (code === "ENOENT" || code === "EACCES" || run.exitCode === 126 || run.exitCode === 127) {
+    return "error:infra";
+  }
+
+  if (upstreamStatus !== undefined) {
+    if (upstreamStatus === 0) return "error:infra";
+    if (upstreamStatus === 429 || upstreamStatus >= 500) return "error:provider";
+    return "failed";
+  }
+
+  const tail = stderr.slice(-FAILURE_TAIL_CHARS);
+  if (INFRA_PATTERNS.some((p) => p.test(tail))) return "error:infra";
+  if (PROVIDER_PATTERNS.some((p) => p.test(tail))) return "error:provider";
+  return "failed";
+}
+
 // =============================================================================
 // Test Execution
 // =============================================================================
 
+/**
+ * Models a scenario runs against when the matrix doesn't name them: all of
+ * them, except that the mock model only plays scenarios with a `mock` script
+ * (without one it just answers "Done.")
+ */
+function defaultModels(config: SuiteConfig, scenario: Scenario): ModelConfig[] {
+  return config.models.filter((m) => m.provider !== "mock" || hasMockScript(scenario));
+}
+
+function hasMockScript(scenario: Scenario): boolean {
+  return Boolean(scenario.mock?.length || scenario.turns?.some((t) => t.mock?.length));
+}
+
 function buildTestPairs(config: SuiteConfig, scenarios: Scenario[]): TestPair[] {
   const modelsByName = new Map(config.models.map((m) => [m.name, m]));
   const runnersByName = new Map(config.runners.map((r) => [r.name, r]));
@@ -804,7 +1512,7 @@ function buildTestPairs(config: SuiteConfig, scenarios: Scenario[]): TestPair[]
 
       const models = entry.models
         ? entry.models.map((n) => modelsByName.get(n)).filter(Boolean) as ModelConfig[]
-        : config.models;
+        : defaultModels(config, scenario);
 
       const runners = entry.runners
         ? entry.runners.map((n) => runnersByName.get(n)).filter(Boolean) as RunnerConfig[]
@@ -821,7 +1529,7 @@ function buildTestPairs(config: SuiteConfig, scenarios: Scenario[]): TestPair[]
 
   // No matrix: all scenarios × all models × all runners
   for (const scenario of scenarios) {
-    for (const model of config.models) {
+    for (const model of defaultModels(config, scenario)) {
       for (const runner of config.runners) {
         pairs.push({ scenario, model, runner });
       }
@@ -831,29 +1539,108 @@ function buildTestPairs(config: SuiteConfig, scenarios: Scenario[]): TestPair[]
 }
 
 function scoreResult(result: TestResultWithLog): number {
+  // Errors say nothing about the model, so any real result is kept over one
+  if (isErrorStatus(result.run.status)) {
+    return Infinity;
+  }
   if (result.run.status === "failed" && result.run.errors?.length) {
     return -1;
   }
-  const passedCount = result.validations.filter((v) => v.passed).length;
   const statusBonus = result.run.status === "passed" ? 1000 : 0;
-  return statusBonus + passedCount;
+  return statusBonus + runScore(result.run.scenario, result.validations);
+}
+
+/** The run's 0-100 score; none for errors, which say nothing about the model */
+function resultScore(result: TestResultWithLog): number | undefined {
+  return isErrorStatus(result.run.status) ? undefined : runScore(result.run.scenario, result.validations);
+}
+
+function mean(values: number[]): number | undefined {
+  return values.length ? values.reduce((a, b) => a + b, 0) / values.length : undefined;
+}
+
+interface ModelRanking {
+  model: string;                          // provider/model
+  perScenario: Array<number | undefined>; // mean score per scenario, in the order given
+  overall: number;                        // mean over all the model's runs
+}
+
+/** Models by mean score, best first; errors are left out */
+function rankModels(results: TestResultWithLog[], scenarios: string[]): ModelRanking[] {
+  const modelOf = (r: TestResultWithLog) => `${r.run.config.provider}/${r.run.config.model}`;
+  const scoresOf = (rs: TestResultWithLog[]) => rs.map(resultScore).filter((x): x is number => x !== undefined);
+  return [...new Set(results.map(modelOf))]
+    .map((model) => {
+      const modelResults = results.filter((r) => modelOf(r) === model);
+      return {
+        model,
+        perScenario: scenarios.map((scenario) => mean(scoresOf(modelResults.filter((r) => r.run.scenario.name === scenario)))),
+        overall: mean(scoresOf(modelResults)),
+      };
+    })
+    .filter((r): r is ModelRanking => r.overall !== undefined)
+    .sort((a, b) => b.overall - a.overall);
+}
+
+function testIdFor(pair: TestPair): string {
+  return `${pair.scenario.name}_${pair.model.name}_${pair.runner.name}`.replace(/[\/\\:]/g, "_");
+}
+
+/** Log of one attempt; retries of an errored attempt get their own (-retryN) */
+function attemptLogFile(pair: TestPair, logsDir: string, attempt: number, retry: number = 0): string {
+  return join(logsDir, `${testIdFor(pair)}_attempt${attempt}${retry ? `-retry${retry}` : ""}.log`);
+}
+
+interface RunOptions {
+  timeoutSeconds: number;
+  stream: boolean;     // echo agent output to the console
+  price?: ModelPrice;  // for the cost of the run
+  judge?: JudgeConfig;
+  retry?: number;      // retries of this attempt so far (after error:infra/provider)
 }
 
 async function runScenario(
   pair: TestPair,
   baseWorkdir: string,
   logsDir: string,
-  attempt: number = 1
+  attempt: number,
+  options: RunOptions
 ): Promise<TestResultWithLog> {
   const { scenario, model, runner } = pair;
-  const testId = `${scenario.name}_${model.name}_${runner.name}`.replace(/[\/\\:]/g, "_");
+  const testId = testIdFor(pair);
   const workdir = join(baseWorkdir, testId);
-  const logFile = join(logsDir, `${testId}_attempt${attempt}.log`);
+  const logFile = attemptLogFile(pair, logsDir, attempt, options.retry);
 
   console.log(`\n▶ ${scenario.name} [${model.provider}/${model.model}] (${runner.name})`);
 
-  setupWorkdir(scenario, workdir);
   mkdirSync(logsDir, { recursive: true });
+  writeFileSync(logFile, options.retry
+    ? `Retry ${options.retry} of attempt ${attempt} (previous run: ${basename(attemptLogFile(pair, logsDir, attempt, options.retry - 1))})\n`
+    : "");
+
+  // Agent output goes to the log as it arrives (so the live report and a
+  // killed run still have it), and optionally to the console line by line
+  let output = "";
+  let agentStderr = "";  // the latest agent invocation's, for classifyFailure
+  let partialLine = "";
+  const append = (text: string) => {
+    output += text;
+    appendFileSync(logFile, text);
+  };
+  const flushLine = () => {
+    if (options.stream && partialLine) console.log(`  │ ${partialLine}`);
+    partialLine = "";
+  };
+  const proc: ProcessOptions = {
+    timeoutMs: options.timeoutSeconds * 1000,
+    onOutput: (chunk) => {
+      append(chunk);
+      if (!options.stream) return;
+      const lines = (partialLine + chunk).split("\n");
+      partialLine = lines.pop()!;
+      for (const line of lines) console.log(`  │ ${line}`);
+    },
+  };
 
   // Create a minimal config for TestRun compatibility
   const config = {
@@ -871,20 +1658,46 @@ async function runScenario(
     status: "running",
   };
 
+  // A fixture that can't be cloned, unpacked or copied is the environment's
+  // fault, not the model's: record it and let the retry loop have a go
+  let snapshot: WorkspaceSnapshot;
+  try {
+    setupWorkdir(scenario, workdir);
+    writeHarnessFiles(scenario, workdir);
+    snapshot = takeSnapshot(workdir);
+  } catch (err) {
+    const message = `Workdir setup failed: ${err instanceof Error ? err.message : String(err)}`;
+    append(`ERROR:\n${message}\n`);
+    console.log(`  error:infra: ${message}`);
+    return {
+      run: { ...run, status: "error:infra", endTime: new Date(), errors: [message] },
+      validations: [],
+      logFile,
+      runnerName: runner.name,
+      toolCalls: 0,
+      turns: 0,
+    };
+  }
+  llmProxy?.begin(testId, attempt);
+
   // Determine if this is a multi-turn or single-turn scenario
   const turns = scenario.turns ?? [
-    { prompt: scenario.prompt!, validate: scenario.validate ?? [] }
+    { prompt: scenario.prompt!, validate: scenario.validate ?? [], mock: scenario.mock }
   ];
   const isMultiTurn = turns.length > 1;
 
-  // For goose/pi: generate session ID upfront
+  // For goose/pi/command: generate session ID upfront
   // For opencode: capture session ID from first turn's output
-  let sessionId: string | undefined = isMultiTurn && (runner.type === "goose" || runner.type === "pi")
+  let sessionId: string | undefined = isMultiTurn && (runner.type === "goose" || runner.type === "pi" || runner.type === "command")
     ? `test_${testId}_${Date.now()}`
     : undefined;
 
-  let output = "";
-  const allValidations: Array<{ rule: any; passed: boolean; message?: string }> = [];
+  const allValidations: Array<{ rule: any; passed: boolean; message?: string; score?: number; error?: boolean }> = [];
+  const rules = scenarioRules(scenario);
+
+  // Workspace changes as the agent left them (before validators run builds etc.)
+  let changes: FileChange[] | undefined;
+  const appendWorkspaceDiff = () => append(WORKSPACE_DIFF_HEADER + formatChanges(changes ?? diffWorkspace(snapshot, workdir)));
 
   try {
     for (let turnIndex = 0; turnIndex < turns.length; turnIndex++) {
@@ -894,27 +1707,52 @@ async function runScenario(
 
       // Run the agent (with session for multi-turn)
       const resume = turnIndex > 0;  // Resume session on turn 2+
-      const result = await runAgent(model, runner, turn.prompt, workdir, sessionId, resume);
-      
+      append(`\n${'='.repeat(60)}\nTURN ${turnIndex + 1}\n${'='.repeat(60)}\n`);
+      mockLlm?.begin(testId, turn.mock ?? []);
+      const result = await runAgent(model, runner, turn.prompt, workdir, proc, sessionId, resume);
+      flushLine();
+
+      agentStderr = result.stderr;
+      run.exitCode = result.exitCode;
+      run.signal = result.signal;
+      run.timedOut = result.timedOut;
+
       // Capture session ID from first turn (for opencode)
       if (turnIndex === 0 && result.sessionId) {
         sessionId = result.sessionId;
       }
-      
-      output += `\n${'='.repeat(60)}\nTURN ${turnIndex + 1}\n${'='.repeat(60)}\n${result.output}`;
+
+      if (result.timedOut) {
+        throw new Error(`Agent timed out after ${options.timeoutSeconds}s`);
+      }
+      if (result.exitCode !== 0) {
+        throw new Error(result.signal
+          ? `Agent was killed by ${result.signal}`
+          : `Agent exited with code ${result.exitCode}`);
+      }
 
       // Validate this turn
-      const turnValidations = validateAll(turn.validate, workdir);
+      changes = diffWorkspace(snapshot, workdir);
+      const turnValidations = await validateAll(turn.validate, {
+        workdir,
+        scenario,
+        transcript: output,
+        turnIndex,
+        changes,
+        judge: options.judge,
+      });
       for (const v of turnValidations) {
         allValidations.push({
           rule: v.rule,
           passed: v.result.passed,
           message: v.result.message,
+          score: v.result.score,
+          error: v.result.error,
         });
       }
 
-      // If any validation failed, stop early
-      const turnPassed = turnValidations.every((v) => v.result.passed);
+      // If any validation that decides the run failed (or couldn't be evaluated), stop early
+      const turnPassed = turnValidations.every((v) => v.result.passed || !failsRun(v.rule, rules));
       if (!turnPassed) {
         console.log(`  Turn ${turnIndex + 1} failed validation`);
         break;
@@ -922,39 +1760,205 @@ async function runScenario(
     }
 
     run.endTime = new Date();
-    const allPassed = allValidations.every((v) => v.passed);
-
-    writeFileSync(logFile, output);
+    const failures = allValidations.filter((v) => !v.passed && failsRun(v.rule, rules));
+    // Only validations that couldn't be evaluated failed: no verdict on the model
+    const validationErrors = failures.length > 0 && failures.every((v) => v.error)
+      ? failures.map((v) => `Validation error: ${validationLabel(v.rule)}: ${v.message ?? "no details"}`)
+      : undefined;
+    if (validationErrors) {
+      append("\n\nERROR:\n" + validationErrors.join("\n"));
+      console.log(`  error:infra: ${validationErrors.join("; ")}`);
+    }
 
     const metrics = parseLogMetrics(output, workdir);
+    const usage = collectUsage(runner, workdir);
+    appendWorkspaceDiff();
     return {
-      run: { ...run, status: allPassed ? "passed" : "failed" },
+      run: validationErrors
+        ? { ...run, status: "error:infra", errors: validationErrors }
+        : { ...run, status: failures.length === 0 ? "passed" : "failed" },
       validations: allValidations,
       logFile,
       runnerName: runner.name,
       toolCalls: metrics.toolCalls,
       turns: metrics.turns,
+      usage,
+      cost: usage && usageCost(usage, options.price),
     };
   } catch (err) {
-    const errorOutput = output + "\n\nERROR:\n" + String(err);
-    writeFileSync(logFile, errorOutput);
+    flushLine();
+    append("\n\nERROR:\n" + String(err));
+
+    const status = classifyFailure(err, run, agentStderr, llmProxy?.lastStatus(testId));
+    // The failed turn never got to diff
+    changes = undefined;
+    appendWorkspaceDiff();
+    if (status !== "failed") {
+      console.log(`  ${status}: ${String(err)}`);
+    }
+    const usage = collectUsage(runner, workdir);
 
     return {
       run: {
         ...run,
-        status: "failed",
+        status,
         endTime: new Date(),
         errors: [String(err)],
       },
       validations: allValidations,
       logFile,
       runnerName: runner.name,
-      toolCalls: parseLogMetrics(errorOutput, workdir).toolCalls,
-      turns: parseLogMetrics(errorOutput, workdir).turns,
+      toolCalls: parseLogMetrics(output, workdir).toolCalls,
+      turns: parseLogMetrics(output, workdir).turns,
+      usage,
+      cost: usage && usageCost(usage, options.price),
     };
   }
 }
 
+// =============================================================================
+// Attempt Aggregation
+// =============================================================================
+
+function aggregateAttempts(attempts: TestResultWithLog[], policy: AggregatePolicy): TestResultWithLog {
+  // Same as scoreResult, except errors rank below everything
+  const bestScore = (r: TestResultWithLog) => (isErrorStatus(r.run.status) ? -Infinity : scoreResult(r));
+
+  switch (policy) {
+    case "best":
+      return attempts.reduce((best, r) => (bestScore(r) > bestScore(best) ? r : best));
+    case "majority": {
+      // Status by majority vote over attempts with a verdict (ties fail), then
+      // the worst attempt with that status
+      const verdicts = attempts.filter((r) => !isErrorStatus(r.run.status));
+      if (verdicts.length === 0) return attempts[0];
+      const passes = verdicts.filter((r) => r.run.status === "passed");
+      const majority = passes.length * 2 > verdicts.length ? passes : verdicts.filter((r) => r.run.status !== "passed");
+      return aggregateAttempts(majority, "worst");
+    }
+    case "worst":
+    default:
+      return attempts.reduce((worst, r) => (scoreResult(r) < scoreResult(worst) ? r : worst));
+  }
+}
+
+function toAttemptRecord(result: TestResultWithLog, attempt: number): AttemptRecord {
+  return {
+    attempt,
+    status: result.run.status,
+    durationMs: result.run.endTime ? result.run.endTime.getTime() - result.run.startTime.getTime() : 0,
+    toolCalls: result.toolCalls,
+    logFile: result.logFile,
+    validations: result.validations.map((v) => ({ label: validationLabel(v.rule), passed: v.passed })),
+    usage: result.usage,
+    cost: result.cost,
+  };
+}
+
+function combinations(n: number, k: number): number {
+  let c = 1;
+  for (let i = 0; i < k; i++) {
+    c = (c * (n - i)) / (i + 1);
+  }
+  return c;
+}
+
+/**
+ * pass@k and pass^k use the unbiased estimators over n attempts with c passes:
+ * pass@k = 1 - C(n-c, k) / C(n, k), pass^k = C(c, k) / C(n, k).
+ */
+function computeAttemptStats(attempts: AttemptRecord[], k: number): AttemptStats | undefined {
+  const verdicts = attempts.filter((a) => !isErrorStatus(a.status));
+  const n = verdicts.length;
+  if (n < 2) return undefined;
+
+  const c = verdicts.filter((a) => a.status === "passed").length;
+  k = Math.max(1, Math.min(k, n));
+
+  // Later-turn validations are missing when an attempt stopped early; those count as failures
+  const reference = verdicts.reduce((longest, a) => (a.validations.length > longest.validations.length ? a : longest));
+  const validations = reference.validations.map((v, i) => ({
+    label: v.label,
+    passes: verdicts.filter((a) => a.validations[i]?.passed).length,
+  }));
+
+  return {
+    n,
+    k,
+    passes: c,
+    passAt1: c / n,
+    passAtK: n - c < k ? 1 : 1 - combinations(n - c, k) / combinations(n, k),
+    passHatK: combinations(c, k) / combinations(n, k),
+    validations,
+    flaky: c > 0 && c < n,
+  };
+}
+
+// =============================================================================
+// Worker Pool
+// =============================================================================
+
+/**
+ * Run pairs on up to `parallel` workers, never exceeding the per-provider limit
+ * from `concurrency`. Queue order is preserved as far as the limits allow, so
+ * pairs for the same model still tend to run back to back. Once a worker
+ * fails no further pairs start, and the pool rejects with the first error as
+ * soon as the ones in flight are done.
+ */
+function runWorkerPool(
+  pairs: TestPair[],
+  parallel: number,
+  concurrency: Record<string, number>,
+  worker: (pair: TestPair) => Promise<void>
+): Promise<void> {
+  const queue = [...pairs];
+  const activeByProvider = new Map<string, number>();
+  let active = 0;
+  let failure: { error: unknown } | undefined;
+
+  const providerLimit = (provider: string) => concurrency[provider] ?? parallel;
+
+  return new Promise((resolve, reject) => {
+    const schedule = () => {
+      if (queue.length === 0 && active === 0) {
+        if (failure) {
+          reject(failure.error);
+        } else {
+          resolve();
+        }
+        return;
+      }
+
+      for (let i = 0; i < queue.length && active < parallel; ) {
+        const pair = queue[i];
+        const provider = pair.model.provider;
+        const inFlight = activeByProvider.get(provider) ?? 0;
+        if (inFlight >= providerLimit(provider)) {
+          i++;
+          continue;
+        }
+
+        queue.splice(i, 1);
+        active++;
+        activeByProvider.set(provider, inFlight + 1);
+
+        const done = () => {
+          active--;
+          activeByProvider.set(provider, activeByProvider.get(provider)! - 1);
+          schedule();
+        };
+        worker(pair).then(done, (error) => {
+          failure ??= { error };
+          queue.length = 0;
+          done();
+        });
+      }
+    };
+
+    schedule();
+  });
+}
+
 // =============================================================================
 // Reporting
 // =============================================================================
@@ -970,6 +1974,7 @@ function resultKey(result: TestResultWithLog): string {
 interface ReportOptions {
   isRunning?: boolean;
   allPairs?: TestPair[];
+  runningPairs?: Map<TestPair, string>;  // pair -> log file being written
 }
 
 function generateHtmlReport(
@@ -977,7 +1982,7 @@ function generateHtmlReport(
   outputPath: string,
   options: ReportOptions = {}
 ): void {
-  const { isRunning = false, allPairs = [] } = options;
+  const { isRunning = false, allPairs = [], runningPairs = new Map() } = options;
 
   // Read and embed gym.png as base64
   const rootDir = join(outputPath, "..");
@@ -992,12 +1997,25 @@ function generateHtmlReport(
   // Collect all logs for embedding
   const logsData: Record<string, string> = {};
   for (const r of results) {
-    if (r.logFile) {
+    const logFiles = [r.logFile, ...(r.attempts ?? []).map((a) => a.logFile)];
+    for (const logFile of logFiles) {
+      if (!logFile || logsData[basename(logFile)]) continue;
       try {
-        logsData[basename(r.logFile)] = readFileSync(r.logFile, "utf-8");
+        const log = readFileSync(logFile, "utf-8");
+        logsData[basename(logFile)] = log;
+        // The workspace diff at the end of the log also gets its own entry
+        const diffAt = log.lastIndexOf(WORKSPACE_DIFF_HEADER);
+        if (diffAt !== -1) {
+          logsData[`${basename(logFile)}.diff`] = log.slice(diffAt + WORKSPACE_DIFF_HEADER.length);
+        }
       } catch (e) { /* ignore missing logs */ }
     }
   }
+  for (const logFile of runningPairs.values()) {
+    try {
+      logsData[basename(logFile)] = readFileSync(logFile, "utf-8");
+    } catch (e) { /* not created yet */ }
+  }
 
   // Calculate max duration for scaling bars
   const maxDuration = Math.max(...results.map(r => {
@@ -1006,6 +2024,9 @@ function generateHtmlReport(
   }), 1);
   
   const maxToolCalls = Math.max(...results.map(r => r.toolCalls || 0), 1);
+  const totalTokens = (r: TestResultWithLog) => (r.usage ? r.usage.input + r.usage.output + r.usage.cacheRead + r.usage.cacheWrite : 0);
+  const maxTokens = Math.max(...results.map(totalTokens), 1);
+  const formatTokens = (n: number) => (n >= 1_000_000 ? `${(n / 1_000_000).toFixed(1)}M` : n >= 1000 ? `${(n / 1000).toFixed(1)}k` : String(n));
 
   // Get all scenarios (columns)
   const scenarios = allPairs.length
@@ -1040,6 +2061,17 @@ function generateHtmlReport(
     validCells.add(`${pair.scenario.name}::${pairKey(pair)}`);
   }
 
+  // Cells with a worker currently on them -> partial log
+  const runningCells = new Map<string, string>();
+  for (const [pair, logFile] of runningPairs) {
+    runningCells.set(`${pair.scenario.name}::${pairKey(pair)}`, basename(logFile));
+  }
+  const logLink = (logName: string) =>
+    `<a class="log-link" href="logs/${logName}" onclick="event.preventDefault();showLog('${logName}')">log</a>`;
+  const diffLink = (logName: string) => logsData[`${logName}.diff`]
+    ? `<a class="log-link" href="logs/${logName}" title="Workspace changes" onclick="event.preventDefault();showLog('${logName}.diff')">diff</a>`
+    : "";
+
   const getResult = (scenario: string, rowKey: string) => {
     const [modelPart, runnerName] = rowKey.split("::");
     return results.find(
@@ -1052,10 +2084,13 @@ function generateHtmlReport(
 
   const passed = results.filter((r) => r.run.status === "passed").length;
   const failed = results.filter((r) => r.run.status === "failed").length;
+  const errored = results.filter((r) => isErrorStatus(r.run.status)).length;
   const total = allPairs.length || results.length;
   const pending = total - results.length;
 
   const runnerNames = [...new Set(allPairs.map((p) => p.runner.name))];
+  const rankings = rankModels(results, scenarios);
+  const formatScore = (x: number | undefined) => (x === undefined ? "—" : String(Math.round(x)));
 
   const html = `<!DOCTYPE html>
 <html lang="en">
@@ -1071,6 +2106,7 @@ function generateHtmlReport(
     .summary { color: #8b949e; margin-bottom: 2rem; font-size: 1.1rem; }
     .summary .passed { color: #3fb950; }
     .summary .failed { color: #f85149; }
+    .summary .errored { color: #a371f7; }
     table { width: 100%; border-collapse: collapse; background: #161b22; border-radius: 8px; overflow: hidden; }
     th, td { padding: 1rem; text-align: left; border-bottom: 1px solid #30363d; }
     th { background: #21262d; color: #58a6ff; font-weight: 600; }
@@ -1080,11 +2116,16 @@ function generateHtmlReport(
     .status { width: 24px; height: 24px; border-radius: 50%; display: flex; align-items: center; justify-content: center; font-size: 14px; }
     .status.passed { background: #238636; }
     .status.failed { background: #da3633; }
+    .status.error { background: #8957e5; }
     .status.pending { background: #6e7681; }
     .status.na { background: transparent; border: 1px dashed #30363d; color: #484f58; }
     .status.running { background: #9e6a03; animation: pulse 1.5s infinite; }
     @keyframes pulse { 0%, 100% { opacity: 1; } 50% { opacity: 0.5; } }
     .cached-badge { background: #388bfd33; color: #58a6ff; font-size: 0.65rem; padding: 0.1rem 0.3rem; border-radius: 3px; margin-left: 0.25rem; }
+    .flaky-badge { background: #d2992233; color: #d29922; font-size: 0.65rem; padding: 0.1rem 0.3rem; border-radius: 3px; }
+    .pass-stats { font-size: 0.7rem; color: #8b949e; margin-top: 2px; }
+    .pass-stats .attempt-links a { margin-left: 0.2rem; }
+    .exit-badge { background: #f8514933; color: #f85149; font-size: 0.65rem; padding: 0.1rem 0.3rem; border-radius: 3px; }
     .duration { color: #8b949e; font-size: 0.85rem; }
     .log-link { color: #58a6ff; font-size: 0.75rem; text-decoration: none; }
     .log-link:hover { text-decoration: underline; }
@@ -1095,6 +2136,10 @@ function generateHtmlReport(
     .metrics { display: flex; gap: 0.5rem; font-size: 0.7rem; color: #8b949e; margin-top: 2px; }
     .metrics .tool-calls { color: #d29922; }
     .metrics .turns { color: #a371f7; }
+    .metrics .tokens { color: #db61a2; }
+    .metrics .cost { color: #3fb950; }
+    .token-bar { height: 4px; background: #30363d; border-radius: 2px; margin-top: 2px; overflow: hidden; }
+    .token-bar-fill { height: 100%; background: #db61a2; border-radius: 2px; }
     .details { font-size: 0.8rem; max-width: 300px; }
     .validation { display: flex; align-items: center; gap: 0.25rem; margin-top: 0.25rem; }
     .validation.pass { color: #3fb950; }
@@ -1108,6 +2153,10 @@ function generateHtmlReport(
     .model-group-start { border-top: 2px solid #30363d; }
     .runner-info { color: #6e7681; font-size: 0.85rem; margin-bottom: 1.5rem; }
     .runner-info code { background: #21262d; padding: 0.2rem 0.4rem; border-radius: 4px; font-family: monospace; }
+    .score { background: #58a6ff22; color: #58a6ff; font-size: 0.7rem; padding: 0.1rem 0.3rem; border-radius: 3px; }
+    .scores-title { color: #58a6ff; font-size: 1.1rem; margin: 2rem 0 0.75rem; }
+    .scores td { font-variant-numeric: tabular-nums; }
+    .scores .overall { font-weight: 600; color: #c9d1d9; }
     .timestamp { color: #6e7681; font-size: 0.9rem; margin-top: 2rem; }
     .header { display: flex; align-items: center; gap: 1rem; margin-bottom: 0.5rem; }
     .header img { height: 48px; width: auto; }
@@ -1129,7 +2178,7 @@ function generateHtmlReport(
   <div class="header"><img src="${gymBase64 ? `data:image/png;base64,${gymBase64}` : 'gym.png'}" alt="Agent Gym"><h1>Agent Gym Workout${isRunning ? " (Running...)" : ""}</h1>${!isRunning ? '<button class="download-btn" onclick="downloadReport()">📥 Download Full Report</button>' : ''}</div>
   <p class="summary">
     <span class="passed">${passed} passed</span> / 
-    <span class="failed">${failed} failed</span>${pending > 0 ? ` / <span style="color:#9e6a03">${pending} pending</span>` : ""} / 
+    <span class="failed">${failed} failed</span>${errored > 0 ? ` / <span class="errored">${errored} errors</span>` : ""}${pending > 0 ? ` / <span style="color:#9e6a03">${pending} pending</span>` : ""} / 
     ${total} total
   </p>
   <p class="runner-info">Agent Configurations: ${runnerNames.map(n => `<code>${n}</code>`).join(", ")}</p>
@@ -1166,6 +2215,8 @@ function generateHtmlReport(
                 const cellKey = `${scenario}::${key}`;
                 const isInMatrix = validCells.has(cellKey);
                 if (!isInMatrix) return `<td><div class="cell"><span class="status na">—</span></div></td>`;
+                const runningLog = runningCells.get(cellKey);
+                if (runningLog) return `<td><div class="cell"><span class="status running">...</span> ${logLink(runningLog)}</div></td>`;
                 return `<td><div class="cell"><span class="status pending">⋯</span></div></td>`;
               }
               if (r.run.status === "running") {
@@ -1174,30 +2225,52 @@ function generateHtmlReport(
               const duration = r.run.endTime
                 ? ((r.run.endTime.getTime() - r.run.startTime.getTime()) / 1000).toFixed(1)
                 : "-";
-              const logPath = r.logFile ? `logs/${basename(r.logFile)}` : "";
-              const validationHtml = r.validations.map((v) => {
+              const isError = isErrorStatus(r.run.status);
+              const exitInfo = isError
+                ? r.run.status.slice("error:".length)
+                : r.run.signal
+                  ? r.run.signal
+                  : r.run.exitCode ? `exit ${r.run.exitCode}` : "";
+              const stats = r.stats;
+              const score = resultScore(r);
+              const validationHtml = r.validations.map((v, i) => {
                 const icon = v.passed ? "✓" : "✗";
                 const cls = v.passed ? "pass" : "fail";
-                const ruleLabel = (v.rule as any).name 
-                  ? (v.rule as any).name
-                  : v.rule.type === "tool_called" 
-                    ? `tool_called: ${(v.rule as any).tool}`
-                    : v.rule.type + (("path" in v.rule) ? `: ${(v.rule as any).path}` : "");
-                return `<div class="validation ${cls}"><span class="validation-icon">${icon}</span> ${ruleLabel}</div>`;
+                const ruleLabel = validationLabel(v.rule);
+                const score = v.score !== undefined ? ` <span class="duration">(${Math.round(v.score * 100)}%)</span>` : "";
+                const rate = stats?.validations[i] ? ` <span class="duration" title="Attempts passing this check">${stats.validations[i].passes}/${stats.n}</span>` : "";
+                // Failure details (e.g. a command's stderr tail) and judge rationales on hover
+                const detail = v.message && (!v.passed || v.rule.type === "judge") ? ` title="${escapeXml(v.message)}"` : "";
+                return `<div class="validation ${cls}"${detail}><span class="validation-icon">${icon}</span> ${ruleLabel}${score}${rate}</div>`;
               }).join("");
+              const pct = (x: number) => `${Math.round(x * 100)}%`;
+              const attemptLinks = (r.attempts ?? [])
+                .filter((a) => logsData[basename(a.logFile)])
+                .map((a) => `<a class="log-link" href="logs/${basename(a.logFile)}" title="${a.status}" onclick="event.preventDefault();showLog('${basename(a.logFile)}')">${a.attempt}</a>`)
+                .join("");
+              const statsHtml = stats
+                ? `<div class="pass-stats" title="${stats.passes}/${stats.n} attempts passed">pass@1 ${pct(stats.passAt1)} · pass@${stats.k} ${pct(stats.passAtK)} · pass^${stats.k} ${pct(stats.passHatK)}${attemptLinks ? ` <span class="attempt-links">attempts:${attemptLinks}</span>` : ""}</div>`
+                : "";
               return `<td>
                 <div class="cell">
-                  <span class="status ${r.run.status}">${r.run.status === "passed" ? "✓" : "✗"}</span>
+                  <span class="status ${isError ? "error" : r.run.status}" title="${r.run.status}">${r.run.status === "passed" ? "✓" : isError ? "!" : "✗"}</span>
                   ${r.cached ? '<span class="cached-badge">cached</span>' : ''}
+                  ${stats?.flaky ? '<span class="flaky-badge">flaky</span>' : ''}
                   <span class="duration">${duration}s</span>
-                  ${logPath ? `<a class="log-link" href="${logPath}" onclick="event.preventDefault();showLog('${basename(r.logFile!)}')">log</a>` : ""}
+                  ${score !== undefined ? `<span class="score" title="Weighted score (0-100)">${score}</span>` : ""}
+                  ${exitInfo ? `<span class="exit-badge">${exitInfo}</span>` : ''}
+                  ${r.logFile ? `${logLink(basename(r.logFile))} ${diffLink(basename(r.logFile))}` : ""}
                 </div>
                 <div class="duration-bar"><div class="duration-bar-fill" style="width: ${Math.round((parseFloat(duration) / maxDuration) * 100)}%"></div></div>
                 <div class="tool-bar"><div class="tool-bar-fill" style="width: ${Math.round(((r.toolCalls || 0) / maxToolCalls) * 100)}%"></div></div>
+                ${r.usage ? `<div class="token-bar"><div class="token-bar-fill" style="width: ${Math.round((totalTokens(r) / maxTokens) * 100)}%"></div></div>` : ""}
                 <div class="metrics">
                   <span class="tool-calls" title="Tool calls">🔧 ${r.toolCalls || 0}</span>
                   <span class="turns" title="Turns">↻ ${r.turns || 0}</span>
+                  ${r.usage ? `<span class="tokens" title="Tokens in / out (cache read ${r.usage.cacheRead}, cache write ${r.usage.cacheWrite})">🪙 ${formatTokens(r.usage.input + r.usage.cacheRead + r.usage.cacheWrite)}/${formatTokens(r.usage.output)}</span>` : ""}
+                  ${r.cost !== undefined ? `<span class="cost" title="Cost (USD)">$${r.cost.toFixed(r.cost < 1 ? 3 : 2)}</span>` : ""}
                 </div>
+                ${statsHtml}
                 <div class="details">${validationHtml}</div>
               </td>`;
             }).join("")}
@@ -1206,6 +2279,27 @@ function generateHtmlReport(
       }).join("")}
     </tbody>
   </table>
+  ${rankings.length ? `
+  <h2 class="scores-title">Scores</h2>
+  <table class="scores">
+    <thead>
+      <tr>
+        <th>#</th>
+        <th>Model</th>
+        ${scenarios.map((s) => `<th>${s}</th>`).join("")}
+        <th>Overall</th>
+      </tr>
+    </thead>
+    <tbody>
+      ${rankings.map((r, i) => `
+      <tr>
+        <td>${i + 1}</td>
+        <td><div class="row-header"><span class="model">${r.model}</span></div></td>
+        ${r.perScenario.map((x) => `<td>${formatScore(x)}</td>`).join("")}
+        <td class="overall">${formatScore(r.overall)}</td>
+      </tr>`).join("")}
+    </tbody>
+  </table>` : ""}
   
   <p class="timestamp">Generated: ${new Date().toISOString()}</p>
 
@@ -1223,7 +2317,9 @@ function generateHtmlReport(
   </div>
 
   <script>
-    const LOGS = ${JSON.stringify(logsData)};
+    // One line, with < escaped so a log can't close the script tag; the live
+    // refresh below reads it back from the fetched page
+    var LOGS = ${JSON.stringify(logsData).replace(/</g, "\\u003c")};
     
     function showLog(logName) {
       const log = LOGS[logName];
@@ -1282,30 +2378,14 @@ function generateHtmlReport(
           document.title = newDoc.title;
           window.scrollTo(0, scrollY);
           
-          // Re-extract LOGS from the new script
+          // Re-extract LOGS from the new script (logs of pairs started since load)
           const newScript = newDoc.querySelector('script');
-          if (newScript) {
-            const scriptText = newScript.textContent;
-            const startMarker = 'const LOGS = ';
-            const startIdx = scriptText.indexOf(startMarker);
-            if (startIdx !== -1) {
-              const jsonStart = startIdx + startMarker.length;
-              let depth = 0;
-              let jsonEnd = jsonStart;
-              for (let i = jsonStart; i < scriptText.length; i++) {
-                if (scriptText[i] === '{') depth++;
-                else if (scriptText[i] === '}') {
-                  depth--;
-                  if (depth === 0) {
-                    jsonEnd = i + 1;
-                    break;
-                  }
-                }
-              }
-              try {
-                window.LOGS = JSON.parse(scriptText.slice(jsonStart, jsonEnd));
-              } catch (e) {}
-            }
+          const marker = 'var LOGS = ';
+          const logsLine = newScript && newScript.textContent.split('\\n').find((line) => line.trim().startsWith(marker));
+          if (logsLine) {
+            try {
+              LOGS = JSON.parse(logsLine.trim().slice(marker.length, -1));
+            } catch (e) {}
           }
           
           if (stillRunning) {
@@ -1323,8 +2403,12 @@ function generateHtmlReport(
 </body>
 </html>`;
 
-  writeFileSync(outputPath, html);
-  console.log(`\n📊 Report saved to: ${outputPath}`);
+  // Write then rename so the live-refreshing page never reads a half-written file
+  writeFileSync(`${outputPath}.tmp`, html);
+  renameSync(`${outputPath}.tmp`, outputPath);
+  if (!isRunning) {
+    console.log(`\n📊 Report saved to: ${outputPath}`);
+  }
 }
 
 function printResults(results: TestResultWithLog[]): void {
@@ -1333,21 +2417,231 @@ function printResults(results: TestResultWithLog[]): void {
   console.log("=".repeat(60));
 
   for (const result of results) {
-    const icon = result.run.status === "passed" ? "✓" : "✗";
+    const icon = result.run.status === "passed" ? "✓" : isErrorStatus(result.run.status) ? "⚠" : "✗";
     const { scenario, config } = result.run;
+    const score = resultScore(result);
     console.log(
-      `${icon} ${scenario.name} [${config.provider}/${config.model}] (${result.runnerName}) - ${result.run.status.toUpperCase()}`
+      `${icon} ${scenario.name} [${config.provider}/${config.model}] (${result.runnerName}) - ${result.run.status.toUpperCase()}${score !== undefined ? ` (score ${score})` : ""}`
     );
 
     for (const v of result.validations) {
       if (!v.passed) {
-        console.log(`    ✗ ${v.message}`);
+        console.log(`    ✗ ${v.message?.replace(/\n/g, "\n      ")}`);
       }
     }
+    if (isErrorStatus(result.run.status)) {
+      for (const e of result.run.errors ?? []) {
+        console.log(`    ⚠ ${e}`);
+      }
+    }
+    if (result.stats) {
+      const { n, k, passes, passAt1, passAtK, passHatK, flaky } = result.stats;
+      const pct = (x: number) => `${Math.round(x * 100)}%`;
+      console.log(`    ${passes}/${n} attempts passed: pass@1 ${pct(passAt1)}, pass@${k} ${pct(passAtK)}, pass^${k} ${pct(passHatK)}${flaky ? " (flaky)" : ""}`);
+    }
   }
 
   const passed = results.filter((r) => r.run.status === "passed").length;
-  console.log(`\n${passed}/${results.length} tests passed`);
+  const errored = results.filter((r) => isErrorStatus(r.run.status)).length;
+  console.log(`\n${passed}/${results.length} tests passed${errored ? ` (${errored} infrastructure/provider errors)` : ""}`);
+
+  const rankings = rankModels(results, []);
+  if (rankings.length > 1) {
+    console.log(`Mean score: ${rankings.map((r) => `${r.model} ${Math.round(r.overall)}`).join(", ")}`);
+  }
+
+  // Every attempt that ran costs money, not just the kept one
+  const pricedAttempts = results
+    .filter((r) => !r.cached)
+    .flatMap((r): Array<{ cost?: number }> => r.attempts ?? [r])
+    .filter((a) => a.cost !== undefined);
+  if (pricedAttempts.length) {
+    const total = pricedAttempts.reduce((sum, a) => sum + a.cost!, 0);
+    console.log(`Cost of this run: $${total.toFixed(2)} (${pricedAttempts.length} priced attempts)`);
+  }
+}
+
+/**
+ * Mismatches between mock runs and their scenario's `expect` (by default the
+ * run passes and so does every validation), one line each
+ */
+function checkExpectations(results: TestResultWithLog[]): string[] {
+  const problems: string[] = [];
+  for (const result of results) {
+    const { scenario, config, status } = result.run;
+    if (config.provider !== "mock") continue;
+    const label = `${scenario.name} (${result.runnerName})`;
+    const expected = scenario.expect?.status ?? "passed";
+    if (status !== expected) {
+      problems.push(`${label}: ${status}, expected ${expected}`);
+    }
+    // An error has no verdicts worth comparing
+    if (isErrorStatus(status)) continue;
+
+    const failing = new Set(result.validations.filter((v) => !v.passed).map((v) => validationLabel(v.rule)));
+    const expectedFailing = new Set(scenario.expect?.failing ?? []);
+    for (const name of expectedFailing) {
+      if (!failing.has(name)) problems.push(`${label}: "${name}" passed, expected to fail`);
+    }
+    for (const name of failing) {
+      if (!expectedFailing.has(name)) problems.push(`${label}: "${name}" failed, expected to pass`);
+    }
+  }
+  return problems;
+}
+
+// =============================================================================
+// Results Export
+// =============================================================================
+
+// results.json and junit.xml sit next to report.html for CI and notebooks
+const RESULTS_VERSION = 1;
+
+interface ExportEntry {
+  pair: TestPair;
+  result: TestResultWithLog;
+  cacheKey: { key: string; inputs: CacheInputs };
+}
+
+function toResultRecord({ pair, result, cacheKey }: ExportEntry) {
+  const { run } = result;
+  return {
+    scenario: pair.scenario.name,
+    model: pair.model,
+    runner: { name: pair.runner.name, type: pair.runner.type, bin: pair.runner.bin },
+    status: run.status,
+    cached: result.cached ?? false,
+    startTime: run.startTime.toISOString(),
+    endTime: run.endTime?.toISOString(),
+    durationMs: run.endTime ? run.endTime.getTime() - run.startTime.getTime() : undefined,
+    toolCalls: result.toolCalls,
+    turns: result.turns,
+    exitCode: run.exitCode,
+    signal: run.signal,
+    timedOut: run.timedOut,
+    errors: run.errors,
+    validations: result.validations,
+    score: resultScore(result),
+    logFile: `logs/${basename(result.logFile)}`,
+    attempts: result.attempts?.map((a) => ({ ...a, logFile: `logs/${basename(a.logFile)}` })),
+    stats: result.stats,
+    usage: result.usage,
+    cost: result.cost,
+    cacheKey: cacheKey.key,
+    inputs: cacheKey.inputs,
+  };
+}
+
+type ResultRecord = ReturnType<typeof toResultRecord>;
+
+function summarizeResults(records: ResultRecord[]): Record<string, number> {
+  return {
+    total: records.length,
+    passed: records.filter((r) => r.status === "passed").length,
+    failed: records.filter((r) => r.status === "failed").length,
+    errors: records.filter((r) => isErrorStatus(r.status)).length,
+    cached: records.filter((r) => r.cached).length,
+    // Every attempt cost money, not just the kept one
+    cost: records
+      .flatMap((r): Array<{ cost?: number }> => r.attempts ?? [r])
+      .reduce((sum, a) => sum + (a.cost ?? 0), 0),
+  };
+}
+
+function writeResultsJson(records: ResultRecord[], outputPath: string): void {
+  writeFileSync(outputPath, JSON.stringify({
+    version: RESULTS_VERSION,
+    generated: new Date().toISOString(),
+    summary: summarizeResults(records),
+    results: records,
+  }, null, 2));
+}
+
+function escapeXml(value: string): string {
+  return value
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;")
+    // Control characters other than tab/newline aren't allowed in XML 1.0
+    .replace(/[\u0000-\u0008\u000B\u000C\u000E-\u001F]/g, "");
+}
+
+/**
+ * One <testsuite> per scenario, one <testcase> per model × runner. Validations
+ * are the testcase's assertions: failed ones become <failure> elements, and the
+ * full list goes to <system-out>.
+ */
+function writeJUnitXml(entries: ExportEntry[], outputPath: string): void {
+  const seconds = (r: TestResultWithLog) =>
+    r.run.endTime ? ((r.run.endTime.getTime() - r.run.startTime.getTime()) / 1000).toFixed(3) : "0";
+
+  const byScenario = new Map<string, ExportEntry[]>();
+  for (const entry of entries) {
+    const name = entry.pair.scenario.name;
+    if (!byScenario.has(name)) byScenario.set(name, []);
+    byScenario.get(name)!.push(entry);
+  }
+
+  const suites = [...byScenario.entries()].map(([scenario, group]) => {
+    const cases = group.map(({ pair, result }) => {
+      const { run, validations } = result;
+      const attrs = [
+        `name="${escapeXml(`${pair.model.name} (${pair.runner.name})`)}"`,
+        `classname="${escapeXml(scenario)}"`,
+        `assertions="${validations.length}"`,
+        `time="${seconds(result)}"`,
+      ].join(" ");
+
+      const body: string[] = [];
+      body.push(`      <properties>
+        <property name="status" value="${escapeXml(run.status)}"/>
+        <property name="cached" value="${result.cached ?? false}"/>
+        <property name="tool_calls" value="${result.toolCalls}"/>
+        <property name="turns" value="${result.turns}"/>${result.usage ? `
+        <property name="input_tokens" value="${result.usage.input}"/>
+        <property name="output_tokens" value="${result.usage.output}"/>
+        <property name="cache_read_tokens" value="${result.usage.cacheRead}"/>
+        <property name="cache_write_tokens" value="${result.usage.cacheWrite}"/>` : ""}${result.cost !== undefined ? `
+        <property name="cost_usd" value="${result.cost}"/>` : ""}${result.stats ? `
+        <property name="pass_at_1" value="${result.stats.passAt1}"/>
+        <property name="pass_at_${result.stats.k}" value="${result.stats.passAtK}"/>
+        <property name="pass_hat_${result.stats.k}" value="${result.stats.passHatK}"/>
+        <property name="flaky" value="${result.stats.flaky}"/>` : ""}
+      </properties>`);
+
+      if (isErrorStatus(run.status)) {
+        const message = run.errors?.[0] ?? run.status;
+        body.push(`      <error type="${escapeXml(run.status)}" message="${escapeXml(message)}"/>`);
+      } else if (run.status === "failed") {
+        const failedChecks = validations.filter((v) => !v.passed);
+        for (const v of failedChecks) {
+          body.push(`      <failure type="${escapeXml(v.rule.type)}" message="${escapeXml(`${validationLabel(v.rule)}: ${v.message ?? "failed"}`)}"/>`);
+        }
+        if (failedChecks.length === 0) {
+          body.push(`      <failure type="error" message="${escapeXml(run.errors?.[0] ?? "failed")}"/>`);
+        }
+      }
+
+      const checks = validations.map((v) => `${v.passed ? "✓" : "✗"} ${validationLabel(v.rule)}${v.message ? ` - ${v.message}` : ""}`);
+      body.push(`      <system-out>${escapeXml([...checks, `log: logs/${basename(result.logFile)}`].join("\n"))}</system-out>`);
+
+      return `    <testcase ${attrs}>\n${body.join("\n")}\n    </testcase>`;
+    });
+
+    const failures = group.filter(({ result }) => result.run.status === "failed").length;
+    const errors = group.filter(({ result }) => isErrorStatus(result.run.status)).length;
+    const time = group.reduce((sum, { result }) => sum + parseFloat(seconds(result)), 0).toFixed(3);
+    return `  <testsuite name="${escapeXml(scenario)}" tests="${group.length}" failures="${failures}" errors="${errors}" time="${time}">\n${cases.join("\n")}\n  </testsuite>`;
+  });
+
+  const failures = entries.filter(({ result }) => result.run.status === "failed").length;
+  const errors = entries.filter(({ result }) => isErrorStatus(result.run.status)).length;
+  writeFileSync(outputPath, `<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="agent-gym" tests="${entries.length}" failures="${failures}" errors="${errors}">
+${suites.join("\n")}
+</testsuites>
+`);
 }
 
 // =============================================================================
@@ -1367,15 +2661,63 @@ async function main() {
   const workdir = join(import.meta.dirname, "../.workdir");
   const logsDir = join(rootDir, "logs");
   const reportPath = join(rootDir, "report.html");
+  const resultsJsonPath = join(rootDir, "results.json");
+  const junitPath = join(rootDir, "junit.xml");
+  const historyDir = join(rootDir, "history");
+  const historyReportPath = join(rootDir, "history.html");
+  const compareReportPath = join(rootDir, "compare.html");
+
+  // CLI --compare=<runA>,<runB>: diff two recorded runs and exit
+  const compareArg = process.argv.find((a) => a.startsWith("--compare="))?.split("=")[1];
+  if (compareArg) {
+    const refs = compareArg.split(",");
+    if (refs.length !== 2) {
+      throw new Error(`--compare takes two run IDs, e.g. --compare=previous,latest`);
+    }
+    const runs = loadHistory(historyDir);
+    const comparison = compareRuns(findRun(runs, refs[0]), findRun(runs, refs[1]));
+    printComparison(comparison);
+    generateComparisonHtml(comparison, compareReportPath);
+    if (!process.argv.includes("--no-open")) {
+      execSync(`open "${compareReportPath}"`);
+    }
+    return;
+  }
+
+  // CLI --history: show pass-rate trends across recorded runs and exit
+  if (process.argv.includes("--history")) {
+    const lastArg = process.argv.find((a) => a.startsWith("--last="))?.split("=")[1];
+    let runs = loadHistory(historyDir);
+    if (lastArg) {
+      runs = runs.slice(-parseInt(lastArg, 10));
+    }
+    if (runs.length === 0) {
+      console.log(`No runs recorded in ${historyDir}`);
+      return;
+    }
+    printHistory(runs);
+    generateHistoryHtml(runs, historyReportPath);
+    if (!process.argv.includes("--no-open")) {
+      execSync(`open "${historyReportPath}"`);
+    }
+    return;
+  }
+
+  const startedAt = new Date();
+  const runId = newRunId(startedAt);
 
   const config = loadConfig(configPath);
-  let scenarios = loadAllScenarios(scenariosDir);
+  const scenarios = loadAllScenarios(scenariosDir);
+
+  // The matrix is checked against everything configured; the CLI filters
+  // then narrow down its pairs (so they can't make a matrix entry "unknown")
+  let pairs = buildTestPairs(config, scenarios);
 
   // CLI --scenario= filter
   const scenarioFilter = process.argv.find((a) => a.startsWith("--scenario="))?.split("=")[1];
   if (scenarioFilter) {
     const filters = scenarioFilter.split(",");
-    scenarios = scenarios.filter((s) => filters.some((f) => s.name.includes(f)));
+    pairs = pairs.filter((p) => filters.some((f) => p.scenario.name.includes(f)));
   }
 
   // CLI --model= filter
@@ -1392,7 +2734,7 @@ async function main() {
     config.runners = config.runners.filter((r) => filters.some((f) => r.name.includes(f)));
   }
 
-  const pairs = buildTestPairs(config, scenarios);
+  pairs = pairs.filter((p) => config.models.includes(p.model) && config.runners.includes(p.runner));
 
   // Sort pairs by model name so same models run together (keeps model loaded in memory)
   pairs.sort((a, b) => a.model.name.localeCompare(b.model.name));
@@ -1407,10 +2749,57 @@ async function main() {
 
   // CLI --run-count=N (default 1)
   const runCountArg = process.argv.find((a) => a.startsWith("--run-count="))?.split("=")[1];
-  const RUN_COUNT = runCountArg ? parseInt(runCountArg, 10) : 1;
+  const RUN_COUNT = runCountArg ? positiveInt(runCountArg, "--run-count") : 1;
+
+  // CLI --aggregate=worst|best|majority: which attempt represents the pair
+  const aggregateArg = process.argv.find((a) => a.startsWith("--aggregate="))?.split("=")[1];
+  const AGGREGATE = (aggregateArg ?? "worst") as AggregatePolicy;
+  if (!AGGREGATE_POLICIES.includes(AGGREGATE)) {
+    throw new Error(`Unknown --aggregate "${aggregateArg}". Supported: ${AGGREGATE_POLICIES.join(", ")}`);
+  }
+
+  // CLI --all-attempts: run every attempt even after a failure (implied by
+  // policies other than worst). Needed for meaningful pass@k.
+  const stopEarly = AGGREGATE === "worst" && !process.argv.includes("--all-attempts");
+
+  // CLI --pass-k=K (default: run count)
+  const passKArg = process.argv.find((a) => a.startsWith("--pass-k="))?.split("=")[1];
+  const PASS_K = passKArg ? positiveInt(passKArg, "--pass-k") : RUN_COUNT;
+
+  // CLI --parallel=N overrides config (default 1 = sequential)
+  const parallelArg = process.argv.find((a) => a.startsWith("--parallel="))?.split("=")[1];
+  const PARALLEL = parallelArg
+    ? positiveInt(parallelArg, "--parallel")
+    : positiveInt(config.parallel ?? 1, "parallel in config.yaml");
+
+  // CLI --timeout=SECONDS overrides runner and config timeouts
+  const timeoutArg = process.argv.find((a) => a.startsWith("--timeout="))?.split("=")[1];
+  const timeoutFor = (runner: RunnerConfig) =>
+    timeoutArg ? parseInt(timeoutArg, 10) : runner.timeout ?? config.timeout ?? DEFAULT_TIMEOUT_SECONDS;
+
+  const RETRY_ATTEMPTS = config.retry?.attempts ?? 2;
+  const RETRY_BACKOFF = config.retry?.backoff ?? 10;
+
+  // CLI --stream: echo agent output to the console while it runs
+  const stream = process.argv.includes("--stream");
+
+  // CLI --record / --replay: route model traffic through the local proxy,
+  // saving every exchange per pair or serving saved ones back (offline).
+  // Both skip cache lookup; replayed results aren't stored in the cache.
+  const proxyMode: ProxyMode | undefined =
+    process.argv.includes("--replay") ? "replay" : process.argv.includes("--record") ? "record" : undefined;
+  if (proxyMode) {
+    llmProxy = await startLlmProxy(proxyMode, RECORDINGS_DIR, { ...DEFAULT_UPSTREAMS, ...config.upstreams });
+    console.log(`LLM proxy: ${proxyMode === "record" ? "recording to" : "replaying from"} ${RECORDINGS_DIR} (${llmProxy.url})`);
+  }
+
+  if (pairs.some((p) => p.model.provider === "mock")) {
+    mockLlm = await startMockLlm();
+    console.log(`Mock provider: ${mockLlm.url}`);
+  }
 
   // CLI --no-cache: skip cache lookup (still stores results)
-  const noCache = process.argv.includes("--no-cache");
+  const noCache = process.argv.includes("--no-cache") || proxyMode !== undefined;
 
   // Load cache and precompute hashes
   const cache = loadCache();
@@ -1425,80 +2814,177 @@ async function main() {
 
   console.log(`Models: ${config.models.map((m) => m.name).join(", ")}`);
   console.log(`Runners: ${config.runners.map((r) => r.name).join(", ")}`);
-  console.log(`Running ${pairs.length} test pairs (${RUN_COUNT}x each, worst result kept)`);
+  console.log(`Running ${pairs.length} test pairs (${RUN_COUNT}x each, ${AGGREGATE} result kept${stopEarly ? ", stopping at the first failure" : ""}, ${PARALLEL} in parallel)`);
+  if (PARALLEL > 1 && config.concurrency) {
+    console.log(`Provider limits: ${Object.entries(config.concurrency).map(([p, n]) => `${p}=${n}`).join(", ")}`);
+  }
   console.log(`Cache: ${noCache ? "disabled" : "enabled"} (${Object.keys(cache.entries).length} entries)`);
 
-  const results: TestResultWithLog[] = [];
+  // Results are slotted by pair index so reports stay in matrix order
+  // even though workers finish out of order
+  const resultSlots: Array<TestResultWithLog | undefined> = new Array(pairs.length);
+  const collectResults = () => resultSlots.filter((r): r is TestResultWithLog => r !== undefined);
+  const pairIndex = new Map(pairs.map((p, i) => [p, i]));
+  const runningPairs = new Map<TestPair, string>();
   
   // CLI --no-open to skip opening browser
   const noOpen = process.argv.includes("--no-open");
 
   let cacheHits = 0;
   let cacheMisses = 0;
-  let browserOpened = false;
 
+  // Resolve cache hits up front; everything else goes to the worker pool
+  const cacheKeys = new Map<TestPair, { key: string; inputs: CacheInputs }>();
+  const pending: TestPair[] = [];
   for (const pair of pairs) {
-    // Check cache first
-    const { key: cacheKey, inputs: cacheInputs } = computeCacheKey(pair, binaryHashes, mcpHarnessHash);
-    
+    const cacheKey = computeCacheKey(pair, binaryHashes, mcpHarnessHash, AGGREGATE, config.judge);
+    cacheKeys.set(pair, cacheKey);
+
     if (!noCache) {
-      const cachedResult = getCachedResult(cache, cacheKey, pair, logsDir);
-      if (cachedResult) {
+      const cachedResult = getCachedResult(cache, cacheKey.key, pair, logsDir, config.pricing?.[pair.model.name]);
+      // A cached result from fewer attempts can't answer a pass@k run
+      const enoughAttempts = stopEarly || (cachedResult?.attempts?.length ?? 1) >= RUN_COUNT;
+      if (cachedResult && enoughAttempts) {
         console.log(`\n${cachedResult.run.status === "passed" ? "✓" : "✗"} ${pair.scenario.name} [${pair.model.name}] (${pair.runner.name}) [CACHED]`);
-        results.push(cachedResult);
+        resultSlots[pairIndex.get(pair)!] = cachedResult;
         cacheHits++;
         continue;
       }
     }
 
-    // First cache miss - generate report with cached results so far and open browser
-    if (!browserOpened) {
-      generateHtmlReport(results, reportPath, { isRunning: true, allPairs: pairs });
-      if (!noOpen) {
-        execSync(`open "${reportPath}"`);
-      }
-      browserOpened = true;
+    pending.push(pair);
+  }
+
+  // Generate report with cached results so far and open browser
+  if (pending.length > 0) {
+    generateHtmlReport(collectResults(), reportPath, { isRunning: true, allPairs: pairs });
+    if (!noOpen) {
+      execSync(`open "${reportPath}"`);
     }
+  }
 
+  installInterruptHandler();
+
+  // Refresh the live report periodically so partial logs show up while agents run
+  const refreshReport = () =>
+    generateHtmlReport(collectResults(), reportPath, { isRunning: true, allPairs: pairs, runningPairs });
+  const reportTimer = setInterval(refreshReport, 5000);
+
+  // A failing worker stops the pool; whatever finished still gets reported
+  let poolError: unknown;
+  await runWorkerPool(pending, PARALLEL, config.concurrency ?? {}, async (pair) => {
     cacheMisses++;
-    let worstResult: TestResultWithLog | null = null;
+    const attempts: TestResultWithLog[] = [];
 
     for (let attempt = 1; attempt <= RUN_COUNT; attempt++) {
       console.log(`  Attempt ${attempt}/${RUN_COUNT} [${pair.runner.name}]`);
-      const result = await runScenario(pair, workdir, logsDir, attempt);
-
-      if (!worstResult) {
-        worstResult = result;
-      } else {
-        const prevScore = scoreResult(worstResult);
-        const currScore = scoreResult(result);
-        if (currScore < prevScore) {
-          worstResult = result;
-        }
+      runningPairs.set(pair, attemptLogFile(pair, logsDir, attempt));
+      refreshReport();
+      const runOptions = {
+        timeoutSeconds: timeoutFor(pair.runner),
+        stream,
+        price: config.pricing?.[pair.model.name],
+        judge: config.judge,
+      };
+      let result = await runScenario(pair, workdir, logsDir, attempt, runOptions);
+
+      // Infra and provider hiccups get retried with exponential backoff
+      for (let retry = 1; retry <= RETRY_ATTEMPTS && RETRYABLE_STATUSES.has(result.run.status); retry++) {
+        const delay = RETRY_BACKOFF * 2 ** (retry - 1);
+        console.log(`  ${result.run.status}, retrying in ${delay}s (${retry}/${RETRY_ATTEMPTS}) [${pair.runner.name}]`);
+        await new Promise((r) => setTimeout(r, delay * 1000));
+        runningPairs.set(pair, attemptLogFile(pair, logsDir, attempt, retry));
+        refreshReport();
+        result = await runScenario(pair, workdir, logsDir, attempt, { ...runOptions, retry });
       }
 
-      if (result.run.status === "failed") {
+      attempts.push(result);
+      if (stopEarly && result.run.status !== "passed") {
         break;
       }
     }
 
-    // Store in cache
-    storeCacheResult(cache, cacheKey, cacheInputs, worstResult!);
+    const attemptRecords = attempts.map((r, i) => toAttemptRecord(r, i + 1));
+    const keptResult: TestResultWithLog = {
+      ...aggregateAttempts(attempts, AGGREGATE),
+      attempts: attemptRecords,
+      stats: computeAttemptStats(attemptRecords, PASS_K),
+    };
 
-    results.push(worstResult!);
-    generateHtmlReport(results, reportPath, { isRunning: true, allPairs: pairs });
-  }
+    // Store in cache (a replay says nothing new about the model)
+    const { key: cacheKey, inputs: cacheInputs } = cacheKeys.get(pair)!;
+    if (proxyMode !== "replay") {
+      storeCacheResult(cache, cacheKey, cacheInputs, keptResult);
+    }
 
+    resultSlots[pairIndex.get(pair)!] = keptResult;
+    runningPairs.delete(pair);
+    refreshReport();
+  })
+    .catch((err) => {
+      poolError = err;
+    })
+    .finally(() => clearInterval(reportTimer));
+  await llmProxy?.close();
+  await mockLlm?.close();
+
+  const results = collectResults();
   generateHtmlReport(results, reportPath, { isRunning: false, allPairs: pairs });
+
+  const exportEntries = pairs.flatMap((pair, i) => {
+    const result = resultSlots[i];
+    return result ? [{ pair, result, cacheKey: cacheKeys.get(pair)! }] : [];
+  });
+  const records = exportEntries.map(toResultRecord);
+  writeResultsJson(records, resultsJsonPath);
+  writeJUnitXml(exportEntries, junitPath);
+  console.log(`📄 Results exported to: ${resultsJsonPath}, ${junitPath}`);
+
+  // CLI --no-history: don't record this run in history/
+  if (!process.argv.includes("--no-history")) {
+    const runDir = recordRun(historyDir, {
+      runId,
+      startedAt: startedAt.toISOString(),
+      finishedAt: new Date().toISOString(),
+      git: gitInfo(rootDir),
+      argv: process.argv.slice(2),
+      config,
+      binaryHashes: Object.fromEntries(binaryHashes),
+      mcpHarnessHash,
+      summary: summarizeResults(records),
+      results: records,
+    }, logsDir, reportPath);
+    console.log(`🗂  Run ${runId} recorded in ${runDir}`);
+  }
   
   // If everything was cached, open browser now with final report
-  if (!browserOpened && !noOpen) {
+  if (pending.length === 0 && !noOpen) {
     execSync(`open "${reportPath}"`);
   }
   
   printResults(results);
 
   console.log(`\nCache summary: ${cacheHits} hits, ${cacheMisses} misses`);
+
+  // CLI --check-expected: fail unless every mock run matched its scenario's
+  // expect (for CI; a run with no mock runs at all doesn't count as a pass)
+  if (process.argv.includes("--check-expected")) {
+    const checked = results.filter((r) => r.run.config.provider === "mock").length;
+    const problems = checked ? checkExpectations(results) : ["no mock model runs to check"];
+    if (problems.length) {
+      console.log(`\n✗ Unexpected mock outcomes:\n${problems.map((p) => `    ${p}`).join("\n")}`);
+      process.exitCode = 1;
+    } else {
+      console.log(`\n✓ All ${checked} mock runs matched their expected outcome`);
+    }
+  }
+
+  if (poolError !== undefined) {
+    throw poolError;
+  }
 }
 
-main().catch(console.error);
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,277 @@
+// Minimal TOML reader for the data_path / data_schema rules: tables, arrays of
+// tables, dotted keys, every string form, integers (incl. hex/octal/binary),
+// floats, booleans, arrays and inline tables. Dates and times stay strings.
+
+type TomlTable = Record<string, unknown>;
+
+const BARE_KEY = /[A-Za-z0-9_-]/;
+const DATETIME = /^(?:\d{4}-\d{2}-\d{2}(?:[Tt ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:[Zz]|[+-]\d{2}:\d{2})?)?|\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?)/;
+const NUMBER = /^(?:[+-]?(?:inf|nan)|0x[0-9A-Fa-f_]+|0o[0-7_]+|0b[01_]+|[+-]?\d[\d_]*(?:\.\d[\d_]*)?(?:[eE][+-]?\d[\d_]*)?)/;
+const ESCAPES: Record<string, string> = { b: "\b", t: "\t", n: "\n", f: "\f", r: "\r", '"': '"', "\\": "\\" };
+
+export function parseToml(source: string): TomlTable {
+  return new TomlParser(source).parse();
+}
+
+class TomlParser {
+  private pos = 0;
+  private readonly root: TomlTable = {};
+
+  constructor(private readonly src: string) {}
+
+  parse(): TomlTable {
+    let current = this.root;
+    for (;;) {
+      this.skipBlank();
+      if (this.pos >= this.src.length) return this.root;
+      if (this.src.startsWith("[[", this.pos)) {
+        this.pos += 2;
+        const keys = this.parseKey();
+        this.expect("]]");
+        current = this.arrayTable(keys);
+      } else if (this.src[this.pos] === "[") {
+        this.pos++;
+        const keys = this.parseKey();
+        this.expect("]");
+        current = this.table(this.root, keys);
+      } else {
+        this.parseKeyValue(current);
+      }
+      this.endOfLine();
+    }
+  }
+
+  private fail(message: string): never {
+    const line = this.src.slice(0, this.pos).split("\n").length;
+    throw new Error(`TOML line ${line}: ${message}`);
+  }
+
+  private expect(token: string): void {
+    this.skipSpaces();
+    if (!this.src.startsWith(token, this.pos)) this.fail(`expected "${token}"`);
+    this.pos += token.length;
+  }
+
+  private skipSpaces(): void {
+    while (this.src[this.pos] === " " || this.src[this.pos] === "\t") this.pos++;
+  }
+
+  /** Spaces, newlines and comments */
+  private skipBlank(): void {
+    for (;;) {
+      const c = this.src[this.pos];
+      if (c === " " || c === "\t" || c === "\r" || c === "\n") {
+        this.pos++;
+      } else if (c === "#") {
+        while (this.pos < this.src.length && this.src[this.pos] !== "\n") this.pos++;
+      } else {
+        return;
+      }
+    }
+  }
+
+  private endOfLine(): void {
+    this.skipSpaces();
+    if (this.src[this.pos] === "#") {
+      while (this.pos < this.src.length && this.src[this.pos] !== "\n") this.pos++;
+    }
+    if (this.src[this.pos] === "\r") this.pos++;
+    if (this.pos < this.src.length && this.src[this.pos] !== "\n") this.fail("expected end of line");
+  }
+
+  private parseKey(): string[] {
+    const keys: string[] = [];
+    for (;;) {
+      this.skipSpaces();
+      const c = this.src[this.pos];
+      if (c === '"') {
+        keys.push(this.basicString());
+      } else if (c === "'") {
+        keys.push(this.literalString());
+      } else {
+        const start = this.pos;
+        while (this.pos < this.src.length && BARE_KEY.test(this.src[this.pos])) this.pos++;
+        if (this.pos === start) this.fail("expected a key");
+        keys.push(this.src.slice(start, this.pos));
+      }
+      this.skipSpaces();
+      if (this.src[this.pos] !== ".") return keys;
+      this.pos++;
+    }
+  }
+
+  private parseKeyValue(target: TomlTable): void {
+    const keys = this.parseKey();
+    this.expect("=");
+    this.skipSpaces();
+    const value = this.parseValue();
+    const table = this.table(target, keys.slice(0, -1));
+    const last = keys[keys.length - 1];
+    if (last in table) this.fail(`duplicate key "${keys.join(".")}"`);
+    table[last] = value;
+  }
+
+  /** Walk (creating as needed) to the table at keys; arrays of tables resolve to their last entry */
+  private table(from: TomlTable, keys: string[]): TomlTable {
+    let table = from;
+    for (const key of keys) {
+      if (!(key in table)) table[key] = {};
+      let next = table[key];
+      if (Array.isArray(next)) next = next[next.length - 1];
+      if (!next || typeof next !== "object") this.fail(`"${key}" is not a table`);
+      table = next as TomlTable;
+    }
+    return table;
+  }
+
+  private arrayTable(keys: string[]): TomlTable {
+    const parent = this.table(this.root, keys.slice(0, -1));
+    const last = keys[keys.length - 1];
+    if (!(last in parent)) parent[last] = [];
+    const array = parent[last];
+    if (!Array.isArray(array)) this.fail(`"${keys.join(".")}" is not an array of tables`);
+    const table: TomlTable = {};
+    array.push(table);
+    return table;
+  }
+
+  private parseValue(): unknown {
+    const rest = this.src.slice(this.pos, this.pos + 64);
+    if (rest.startsWith('"""')) return this.multilineBasicString();
+    if (rest.startsWith("'''")) return this.multilineLiteralString();
+    if (rest[0] === '"') return this.basicString();
+    if (rest[0] === "'") return this.literalString();
+    if (rest[0] === "[") return this.array();
+    if (rest[0] === "{") return this.inlineTable();
+    if (/^true(?![\w-])/.test(rest)) {
+      this.pos += 4;
+      return true;
+    }
+    if (/^false(?![\w-])/.test(rest)) {
+      this.pos += 5;
+      return false;
+    }
+
+    const datetime = DATETIME.exec(this.src.slice(this.pos, this.pos + 40));
+    if (datetime && /[-:]/.test(datetime[0])) {
+      this.pos += datetime[0].length;
+      return datetime[0];
+    }
+
+    const number = NUMBER.exec(rest);
+    if (!number) this.fail("expected a value");
+    this.pos += number[0].length;
+    const text = number[0].replace(/_/g, "");
+    if (/inf$/.test(text)) return text.startsWith("-") ? -Infinity : Infinity;
+    if (/nan$/.test(text)) return NaN;
+    if (/^0[xob]/.test(text)) return Number(text);
+    return /[.eE]/.test(text) ? parseFloat(text) : parseInt(text, 10);
+  }
+
+  private basicString(): string {
+    this.pos++;  // opening "
+    let out = "";
+    for (;;) {
+      const c = this.src[this.pos++];
+      if (c === undefined || c === "\n") this.fail("unterminated string");
+      if (c === '"') return out;
+      out += c === "\\" ? this.escape() : c;
+    }
+  }
+
+  private escape(): string {
+    const c = this.src[this.pos++];
+    if (c in ESCAPES) return ESCAPES[c];
+    if (c === "u" || c === "U") {
+      const length = c === "u" ? 4 : 8;
+      const hex = this.src.slice(this.pos, this.pos + length);
+      this.pos += length;
+      return String.fromCodePoint(parseInt(hex, 16));
+    }
+    return this.fail(`invalid escape "\\${c}"`);
+  }
+
+  private multilineBasicString(): string {
+    this.pos += 3;
+    if (this.src[this.pos] === "\r") this.pos++;
+    if (this.src[this.pos] === "\n") this.pos++;
+    let out = "";
+    for (;;) {
+      if (this.pos >= this.src.length) this.fail("unterminated string");
+      if (this.src.startsWith('"""', this.pos)) {
+        // Up to two quotes right before the closing ones belong to the string
+        let end = this.pos + 3;
+        while (this.src[end] === '"' && end < this.pos + 5) end++;
+        out += this.src.slice(this.pos, end - 3);
+        this.pos = end;
+        return out;
+      }
+      const c = this.src[this.pos++];
+      if (c !== "\\") {
+        out += c;
+      } else if (/^[ \t]*\r?\n/.test(this.src.slice(this.pos))) {
+        // Line-ending backslash: drop the newline and leading whitespace
+        while (/[ \t\r\n]/.test(this.src[this.pos] ?? "")) this.pos++;
+      } else {
+        out += this.escape();
+      }
+    }
+  }
+
+  private literalString(): string {
+    const end = this.src.indexOf("'", this.pos + 1);
+    if (end === -1 || this.src.slice(this.pos, end).includes("\n")) this.fail("unterminated string");
+    const out = this.src.slice(this.pos + 1, end);
+    this.pos = end + 1;
+    return out;
+  }
+
+  private multilineLiteralString(): string {
+    let start = this.pos + 3;
+    if (this.src[start] === "\r") start++;
+    if (this.src[start] === "\n") start++;
+    let end = this.src.indexOf("'''", start);
+    if (end === -1) this.fail("unterminated string");
+    // Up to two quotes right before the closing ones belong to the string
+    for (let extra = 0; extra < 2 && this.src[end + 3] === "'"; extra++) end++;
+    this.pos = end + 3;
+    return this.src.slice(start, end);
+  }
+
+  private array(): unknown[] {
+    this.pos++;  // [
+    const items: unknown[] = [];
+    for (;;) {
+      this.skipBlank();
+      if (this.src[this.pos] === "]") {
+        this.pos++;
+        return items;
+      }
+      items.push(this.parseValue());
+      this.skipBlank();
+      if (this.src[this.pos] === ",") {
+        this.pos++;
+      } else if (this.src[this.pos] !== "]") {
+        this.fail('expected "," or "]"');
+      }
+    }
+  }
+
+  private inlineTable(): TomlTable {
+    this.pos++;  // {
+    const table: TomlTable = {};
+    this.skipSpaces();
+    if (this.src[this.pos] === "}") {
+      this.pos++;
+      return table;
+    }
+    for (;;) {
+      this.parseKeyValue(table);
+      this.skipSpaces();
+      const c = this.src[this.pos++];
+      if (c === "}") return table;
+      if (c !== ",") this.fail('expected "," or "}"');
+      this.skipSpaces();
+    }
+  }
+}
@@ -14,34 +14,229 @@ export interface AgentConfig {
 export interface Scenario {
   name: string;
   description: string;
+  /** Directory containing the scenario file (set by the loader) */
+  dir?: string;
   prompt?: string;
   /** Files to create before running (relative paths) */
   setup?: Record<string, string>;
+  /** Directory copied into the workdir (relative to the scenario file) */
+  setup_dir?: string;
+  /** Tar archive (.tar, .tar.gz, .tgz, .tar.xz, ...) extracted into the workdir (relative to the scenario file) */
+  setup_archive?: string;
+  /** Git repository or bundle checked out as the workdir (relative to the scenario file) */
+  setup_git?: { repo: string; ref?: string };
+  /** Seed data for the MCP harness, replacing its defaults (e.g. slack.messages, jira.issues) */
+  fixtures?: HarnessFixtures;
+  /** Faults the MCP harness injects into tool calls */
+  faults?: FaultProfile;
+  /** Frozen "current time" for the MCP harness (ISO 8601) */
+  now?: string;
+  /** RNG seed for harness IDs and fault rolls (default 1) */
+  seed?: number;
   /** Validation rules to check after agent completes (single-turn) */
   validate?: ValidationRule[];
   /** Multi-turn conversation (alternative to single prompt+validate) */
   turns?: Turn[];
+  /** Replies of the `mock` provider, one per model request (single-turn) */
+  mock?: MockStep[];
+  /** Outcome of the scripted `mock` run, checked with --check-expected */
+  expect?: MockExpectation;
   /** Tags for filtering scenarios */
   tags?: string[];
 }
 
+/** MCP harness seed data: app -> collection -> records */
+export type HarnessFixtures = Record<string, Record<string, unknown>>;
+
+/** Deterministic tool failures injected by the MCP harness */
+export interface FaultProfile {
+  /** Seed for `probability` rolls (default: the scenario's `seed`) */
+  seed?: number;
+  /** Add 50-250ms of latency to every call */
+  latency?: boolean;
+  rules: FaultRule[];
+}
+
+export interface FaultRule {
+  /** Tool name; `*` wildcards allowed (e.g. "jira_*") */
+  tool: string;
+  /** What goes wrong (default "error") */
+  kind?: "error" | "rate_limit" | "malformed" | "timeout";
+  /** Only fire on these 1-based call numbers of the tool */
+  on_call?: number | number[];
+  /** Chance of firing per eligible call */
+  probability?: number;
+  message?: string;
+  /** Status code for "error" faults (default 500) */
+  status?: number;
+  /** Seconds reported by "rate_limit" faults (default 30) */
+  retry_after?: number;
+  /** How long "timeout" faults hang before answering (default 120000) */
+  hang_ms?: number;
+}
+
 /** A single turn in a multi-turn conversation */
 export interface Turn {
   /** The prompt for this turn */
   prompt: string;
   /** Validation rules to check after this turn completes */
   validate: ValidationRule[];
+  /** Replies of the `mock` provider during this turn */
+  mock?: MockStep[];
+}
+
+/** One scripted reply of the `mock` provider */
+export interface MockStep {
+  /** Assistant text */
+  content?: string;
+  /** Tool calls to make; the agent's tool-name prefix can be left off */
+  tool_calls?: Array<{ name: string; arguments?: Record<string, unknown> }>;
 }
 
-export type ValidationRule =
+/** Expected outcome of a scenario's scripted `mock` run */
+export interface MockExpectation {
+  /** Run status (default "passed") */
+  status?: "passed" | "failed";
+  /** Labels of the top-level validations that fail; every other one passes */
+  failing?: string[];
+}
+
+/** Scoring options every validation rule accepts */
+export interface RuleScoring {
+  /** Share of the run's 0-100 score (default 1) */
+  weight?: number;
+  /**
+   * A failed critical check fails the run. Once any rule of a scenario is
+   * critical, the others only count towards the score.
+   */
+  critical?: boolean;
+}
+
+export type ValidationRule = RuleScoring & (
   | { type: "file_exists"; path: string; name?: string }
   | { type: "file_contains"; path: string; pattern: string; name?: string }
   | { type: "file_matches"; path: string; regex: string; name?: string }
   | { type: "file_not_matches"; path: string; regex: string; name?: string }
   | { type: "file_not_empty"; path: string; name?: string }
   | { type: "command_succeeds"; command: string; name?: string }
+  | {
+      type: "command_output";
+      command: string;
+      /** Default 0 */
+      exit_code?: number;
+      stdout?: OutputMatch;
+      stderr?: OutputMatch;
+      /** Seconds (default 300) */
+      timeout?: number;
+      env?: Record<string, string>;
+      name?: string;
+    }
+  | {
+      type: "data_path";
+      path: string;
+      /** JSONPath-like: $.a.b, $.list[0], $.list[*].name, $..name */
+      select: string;
+      /** Default: from the file extension */
+      format?: "json" | "yaml" | "toml";
+      /** false = the selector must match nothing (default true) */
+      exists?: boolean;
+      equals?: unknown;
+      regex?: string;
+      min?: number;
+      max?: number;
+      length?: number;
+      min_length?: number;
+      max_length?: number;
+      name?: string;
+    }
+  | { type: "data_schema"; path: string; schema: string; format?: "json" | "yaml" | "toml"; name?: string }
+  | { type: "files_unchanged"; paths: string[]; name?: string }
+  | { type: "only_changed"; paths: string[]; name?: string }
+  | { type: "no_new_files"; except?: string[]; name?: string }
+  | { type: "max_diff_lines"; max: number; paths?: string[]; name?: string }
   | { type: "tool_called"; tool: string; args?: Record<string, string | RegExp>; name?: string }
-  | { type: "custom"; fn: string; name?: string };
+  | { type: "tool_not_called"; tool: string | string[]; args?: Record<string, string | RegExp>; name?: string }
+  | { type: "tool_sequence"; tools: Array<string | ToolMatch>; name?: string }
+  | {
+      type: "tool_call_count";
+      tool: string;
+      args?: Record<string, string | RegExp>;
+      count?: number;
+      min?: number;
+      max?: number;
+      name?: string;
+    }
+  | { type: "tool_arg_from_result"; tool: string; arg: string; from: string; path?: string; name?: string }
+  | {
+      type: "judge";
+      /** What a good result looks like, in plain words */
+      rubric: string;
+      /** What to grade (default: the file at `path` if given, else the transcript) */
+      input?: "file" | "transcript" | "tool_calls";
+      path?: string;
+      /** Minimum score to pass, 0-1 (default 0.7) */
+      threshold?: number;
+      name?: string;
+    }
+  | { type: "custom"; fn: string; name?: string }
+  // Combinators over nested rules (their own weight/critical are ignored)
+  | { type: "all_of"; rules: ValidationRule[]; name?: string }
+  | { type: "any_of"; rules: ValidationRule[]; name?: string }
+  | { type: "not"; rule: ValidationRule; name?: string }
+);
+
+/** Checks on a command's stdout or stderr; every given check must hold */
+export interface OutputMatch {
+  contains?: string | string[];
+  regex?: string;
+}
+
+/** A tool plus optional argument patterns (same matching as `tool_called`) */
+export interface ToolMatch {
+  tool: string;
+  args?: Record<string, string | RegExp>;
+}
+
+/** One line of tool-calls.log, as written by the MCP harness */
+export interface ToolCall {
+  timestamp: string;
+  tool: string;
+  arguments: Record<string, any>;
+  result: any;
+  /** Present when the harness injected a fault into this call */
+  fault?: { kind: string; rule: number; call: number; [key: string]: any };
+}
+
+/** Everything a custom validator module gets to look at */
+export interface CustomValidatorContext {
+  workdir: string;
+  toolCalls: ToolCall[];
+  /** Agent output up to and including this turn */
+  transcript: string;
+  /** 0-based turn index (always 0 for single-turn scenarios) */
+  turnIndex: number;
+  scenario: Scenario;
+}
+
+export interface CustomValidatorResult {
+  passed: boolean;
+  message?: string;
+  /** Optional 0-1 score for partial credit */
+  score?: number;
+}
+
+/** Signature of the function a `custom` rule's `fn` points at */
+export type CustomValidator = (
+  ctx: CustomValidatorContext
+) => CustomValidatorResult | Promise<CustomValidatorResult>;
+
+/**
+ * Runs that never got a verdict on the model: the agent couldn't start or
+ * reach its backend (infra), the provider API failed (provider), or it ran
+ * past the timeout. None of these are cached; infra and provider errors are
+ * retried.
+ */
+export type ErrorStatus = "error:infra" | "error:timeout" | "error:provider";
 
 export interface TestRun {
   scenario: Scenario;
@@ -49,8 +244,14 @@ export interface TestRun {
   workdir: string;
   startTime: Date;
   endTime?: Date;
-  status: "pending" | "running" | "passed" | "failed";
+  status: "pending" | "running" | "passed" | "failed" | ErrorStatus;
   errors?: string[];
+  /** Exit code of the last agent invocation (null when killed by a signal) */
+  exitCode?: number | null;
+  /** Signal that terminated the last agent invocation */
+  signal?: string | null;
+  /** The last agent invocation hit the timeout and was killed */
+  timedOut?: boolean;
 }
 
 export interface TestResult {
@@ -59,6 +260,9 @@ export interface TestResult {
     rule: ValidationRule;
     passed: boolean;
     message?: string;
+    score?: number;
+    /** The rule couldn't be evaluated, so `passed` says nothing about the model */
+    error?: boolean;
   }>;
 }
 
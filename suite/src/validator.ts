@@ -1,17 +1,213 @@
 import { existsSync, readFileSync, statSync } from "node:fs";
 import { execSync } from "node:child_process";
-import { join } from "node:path";
-import type { ValidationRule } from "./types.js";
+import { join, resolve } from "node:path";
+import { pathToFileURL } from "node:url";
+import type { CustomValidator, OutputMatch, Scenario, ToolCall, ToolMatch, ValidationRule } from "./types.js";
+import { pathMatches, type FileChange } from "./workspace.js";
+import { checkSchema, deepEqual, loadDataFile, select, showValue, unsupportedKeywords } from "./data.js";
+import { judge, type JudgeConfig } from "./judge.js";
+import { runProcess, type ProcessResult } from "./process.js";
 
 export interface ValidationResult {
   passed: boolean;
   message?: string;
+  score?: number;
+  /** The rule couldn't be evaluated (missing file or log, no grader, bad schema, ...): not a verdict */
+  error?: boolean;
 }
 
-export function validateRule(
+export interface ValidationContext {
+  workdir: string;
+  scenario: Scenario;
+  /** Agent output up to and including the current turn */
+  transcript: string;
+  turnIndex: number;
+  /** Workdir changes since setup, as the agent left it this turn */
+  changes?: FileChange[];
+  /** Grader for `judge` rules (config.yaml `judge:`) */
+  judge?: JudgeConfig;
+}
+
+// Failure messages quote at most this many diff lines
+const MAX_MESSAGE_DIFF_LINES = 40;
+
+/** "<summary>\n<unified diff>", with the diff cut short if long */
+function changesMessage(summary: string, changes: FileChange[]): string {
+  const lines = changes.map((c) => c.diff).join("").trimEnd().split("\n");
+  const shown = lines.slice(0, MAX_MESSAGE_DIFF_LINES).join("\n");
+  const more = lines.length > MAX_MESSAGE_DIFF_LINES ? `\n... (${lines.length - MAX_MESSAGE_DIFF_LINES} more lines)` : "";
+  return `${summary}\n${shown}${more}`;
+}
+
+function describeChanges(changes: FileChange[]): string {
+  return changes.map((c) => `${c.path} (${c.kind})`).join(", ");
+}
+
+/**
+ * Parse tool-calls.log from the workdir. The harness only writes it on a call,
+ * so a missing log means no calls
+ */
+export function readToolCalls(workdir: string): ToolCall[] {
+  const logPath = join(workdir, "tool-calls.log");
+  if (!existsSync(logPath)) {
+    return [];
+  }
+
+  const content = readFileSync(logPath, "utf-8");
+  return content
+    .trim()
+    .split("\n")
+    .filter(Boolean)
+    .map((line) => {
+      try {
+        return JSON.parse(line) as ToolCall;
+      } catch {
+        return null;
+      }
+    })
+    .filter((entry): entry is ToolCall => entry !== null);
+}
+
+/**
+ * Calls that actually ran the tool: a malformed response still ran it, other
+ * injected faults didn't
+ */
+function callsThatRan(toolCalls: ToolCall[]): ToolCall[] {
+  return toolCalls.filter((entry) => !entry.fault || entry.fault.kind === "malformed");
+}
+
+/**
+ * Check call arguments against expected values: "/regex/" patterns match
+ * case-insensitively, anything else is a case-insensitive substring
+ */
+function argsMatch(args: Record<string, any>, expected: Record<string, string | RegExp>): boolean {
+  for (const [key, want] of Object.entries(expected)) {
+    const actual = args[key];
+    if (actual === undefined) {
+      return false;
+    }
+
+    // If expected starts/ends with /, treat as regex pattern
+    if (typeof want === "string" && want.startsWith("/") && want.endsWith("/")) {
+      const pattern = new RegExp(want.slice(1, -1), "i");
+      if (!pattern.test(String(actual))) {
+        return false;
+      }
+    } else if (!String(actual).toLowerCase().includes(String(want).toLowerCase())) {
+      return false;
+    }
+  }
+  return true;
+}
+
+/** Tool-name glob with `*` wildcards, e.g. "*_delete_*" (same syntax as fault rules) */
+function globMatches(pattern: string, name: string): boolean {
+  const regex = pattern.split("*").map((part) => part.replace(/[.+?^${}()|[\]\\]/g, "\\$&")).join(".*");
+  return new RegExp(`^${regex}$`).test(name);
+}
+
+function callMatches(call: ToolCall, match: ToolMatch): boolean {
+  return call.tool === match.tool && (!match.args || argsMatch(call.arguments || {}, match.args));
+}
+
+/** "tool({...args})", for quoting calls in failure messages */
+function formatToolCall(call: ToolCall): string {
+  return `${call.tool}(${JSON.stringify(call.arguments ?? {})})`;
+}
+
+/**
+ * Values in a tool result at a path like "messages.matches[*].user" ("*"
+ * fans out over arrays and objects). Without a path: every scalar in the result.
+ */
+function valuesAtPath(value: unknown, path?: string): unknown[] {
+  if (!path) {
+    return value !== null && typeof value === "object" ? Object.values(value).flatMap((v) => valuesAtPath(v)) : [value];
+  }
+  let current: unknown[] = [value];
+  for (const key of path.replace(/\[([^\]]+)\]/g, ".$1").split(".").filter(Boolean)) {
+    current = current.flatMap((v) => {
+      if (v === null || typeof v !== "object") return [];
+      if (key === "*") return Object.values(v);
+      return key in v ? [(v as Record<string, unknown>)[key]] : [];
+    });
+  }
+  return current;
+}
+
+const DEFAULT_COMMAND_TIMEOUT_SECONDS = 300;
+// Lines of stderr (or stdout when stderr is empty) quoted in command_output failures
+const OUTPUT_TAIL_LINES = 20;
+
+function outputTail(text: string): string {
+  const lines = text.trimEnd().split("\n");
+  const tail = lines.slice(-OUTPUT_TAIL_LINES).join("\n");
+  return lines.length > OUTPUT_TAIL_LINES ? `...\n${tail}` : tail;
+}
+
+/** What's wrong with a command's stdout/stderr (undefined = fine) */
+function outputProblem(stream: string, text: string, match: OutputMatch | undefined): string | undefined {
+  if (!match) return undefined;
+  for (const needle of [match.contains ?? []].flat()) {
+    if (!text.includes(needle)) return `${stream} doesn't contain "${needle}"`;
+  }
+  if (match.regex !== undefined && !new RegExp(match.regex, "m").test(text)) {
+    return `${stream} doesn't match /${match.regex}/`;
+  }
+  return undefined;
+}
+
+const DEFAULT_JUDGE_THRESHOLD = 0.7;
+
+// Failure messages list at most this many schema violations
+const MAX_SCHEMA_ERRORS = 10;
+
+type DataPathRule = Extract<ValidationRule, { type: "data_path" }>;
+
+/** What's wrong with a selected value under a data_path rule (undefined = fine) */
+function dataValueProblem(rule: DataPathRule, value: unknown): string | undefined {
+  if (rule.equals !== undefined && !deepEqual(value, rule.equals)) {
+    return `expected ${showValue(rule.equals)}`;
+  }
+  if (rule.regex !== undefined && !new RegExp(rule.regex).test(typeof value === "string" ? value : JSON.stringify(value))) {
+    return `expected to match /${rule.regex}/`;
+  }
+  if (rule.min !== undefined || rule.max !== undefined) {
+    if (typeof value !== "number") return "expected a number";
+    if (rule.min !== undefined && value < rule.min) return `expected at least ${rule.min}`;
+    if (rule.max !== undefined && value > rule.max) return `expected at most ${rule.max}`;
+  }
+  if (rule.length !== undefined || rule.min_length !== undefined || rule.max_length !== undefined) {
+    const length = Array.isArray(value) || typeof value === "string"
+      ? value.length
+      : value !== null && typeof value === "object" ? Object.keys(value).length : undefined;
+    if (length === undefined) return "expected an array, string or object";
+    if (rule.length !== undefined && length !== rule.length) return `length ${length}, expected ${rule.length}`;
+    if (rule.min_length !== undefined && length < rule.min_length) return `length ${length}, expected at least ${rule.min_length}`;
+    if (rule.max_length !== undefined && length > rule.max_length) return `length ${length}, expected at most ${rule.max_length}`;
+  }
+  return undefined;
+}
+
+/**
+ * Load a custom validator from "path/to/module.ts#exportName" (export defaults
+ * to "default"). Paths are relative to the scenario file's directory.
+ */
+async function loadCustomValidator(fn: string, scenarioDir: string): Promise<CustomValidator> {
+  const [modulePath, exportName = "default"] = fn.split("#");
+  const mod = await import(pathToFileURL(resolve(scenarioDir, modulePath)).href);
+  const validator = mod[exportName];
+  if (typeof validator !== "function") {
+    throw new Error(`${modulePath} has no exported function "${exportName}"`);
+  }
+  return validator;
+}
+
+export async function validateRule(
   rule: ValidationRule,
-  workdir: string
-): ValidationResult {
+  ctx: ValidationContext
+): Promise<ValidationResult> {
+  const { workdir } = ctx;
+
   switch (rule.type) {
     case "file_exists": {
       const fullPath = join(workdir, rule.path);
@@ -25,7 +221,7 @@ export function validateRule(
     case "file_not_empty": {
       const fullPath = join(workdir, rule.path);
       if (!existsSync(fullPath)) {
-        return { passed: false, message: `File not found: ${rule.path}` };
+        return { passed: false, error: true, message: `File not found: ${rule.path}` };
       }
       const stat = statSync(fullPath);
       return {
@@ -37,7 +233,7 @@ export function validateRule(
     case "file_contains": {
       const fullPath = join(workdir, rule.path);
       if (!existsSync(fullPath)) {
-        return { passed: false, message: `File not found: ${rule.path}` };
+        return { passed: false, error: true, message: `File not found: ${rule.path}` };
       }
       const content = readFileSync(fullPath, "utf-8");
       const contains = content.includes(rule.pattern);
@@ -52,7 +248,7 @@ export function validateRule(
     case "file_matches": {
       const fullPath = join(workdir, rule.path);
       if (!existsSync(fullPath)) {
-        return { passed: false, message: `File not found: ${rule.path}` };
+        return { passed: false, error: true, message: `File not found: ${rule.path}` };
       }
       const content = readFileSync(fullPath, "utf-8");
       const regex = new RegExp(rule.regex);
@@ -68,7 +264,7 @@ export function validateRule(
     case "file_not_matches": {
       const fullPath = join(workdir, rule.path);
       if (!existsSync(fullPath)) {
-        return { passed: false, message: `File not found: ${rule.path}` };
+        return { passed: false, error: true, message: `File not found: ${rule.path}` };
       }
       const content = readFileSync(fullPath, "utf-8");
       const regex = new RegExp(rule.regex);
@@ -93,92 +289,421 @@ export function validateRule(
       }
     }
 
-    case "tool_called": {
-      const logPath = join(workdir, "tool-calls.log");
-      if (!existsSync(logPath)) {
-        return { passed: false, message: "tool-calls.log not found" };
+    case "command_output": {
+      // In its own process group, like the agents: a timeout takes down
+      // whatever the command started, and other pairs keep running meanwhile
+      const timeout = rule.timeout ?? DEFAULT_COMMAND_TIMEOUT_SECONDS;
+      let result: ProcessResult;
+      try {
+        result = await runProcess(rule.command, [], {
+          cwd: workdir,
+          env: { ...process.env, ...rule.env },
+          shell: "/bin/sh",
+        }, { timeoutMs: timeout * 1000, name: "validation command" });
+      } catch (err) {
+        return { passed: false, error: true, message: `${rule.command}: ${err instanceof Error ? err.message : String(err)}` };
+      }
+      const { stdout, stderr } = result;
+      const expected = rule.exit_code ?? 0;
+
+      let problem: string | undefined;
+      if (result.timedOut) {
+        problem = `timed out after ${timeout}s`;
+      } else if (result.exitCode === null) {
+        problem = `killed by ${result.signal}`;
+      } else if (result.exitCode !== expected) {
+        problem = `exit code ${result.exitCode}, expected ${expected}`;
+      } else {
+        problem = outputProblem("stdout", stdout, rule.stdout) ?? outputProblem("stderr", stderr, rule.stderr);
+      }
+      if (!problem) return { passed: true };
+
+      // Quote stdout when that's what failed (or there's no stderr), stderr otherwise
+      const [stream, text] = problem.startsWith("stdout") || !stderr.trim() ? ["stdout", stdout] : ["stderr", stderr];
+      const tail = text.trim() ? `\n${stream}:\n${outputTail(text)}` : "";
+      return { passed: false, error: result.timedOut, message: `${rule.command}: ${problem}${tail}` };
+    }
+
+    case "data_path":
+    case "data_schema": {
+      const fullPath = join(workdir, rule.path);
+      if (!existsSync(fullPath)) {
+        return { passed: false, error: true, message: `File not found: ${rule.path}` };
+      }
+      let data: unknown;
+      try {
+        data = loadDataFile(fullPath, rule.format);
+      } catch (err) {
+        return { passed: false, error: true, message: `Can't parse ${rule.path}: ${err instanceof Error ? err.message : String(err)}` };
+      }
+
+      if (rule.type === "data_schema") {
+        // Schemas live next to the scenario, as JSON or YAML
+        let schema: unknown;
+        try {
+          schema = loadDataFile(resolve(ctx.scenario.dir ?? process.cwd(), rule.schema));
+        } catch (err) {
+          return { passed: false, error: true, message: `Can't load schema ${rule.schema}: ${err instanceof Error ? err.message : String(err)}` };
+        }
+        const unsupported = unsupportedKeywords(schema);
+        if (unsupported.length) {
+          return { passed: false, error: true, message: `Schema ${rule.schema} uses keywords the checker can't evaluate: ${unsupported.join(", ")}` };
+        }
+        const errors = checkSchema(data, schema);
+        const more = errors.length > MAX_SCHEMA_ERRORS ? `\n... (${errors.length - MAX_SCHEMA_ERRORS} more)` : "";
+        return errors.length === 0
+          ? { passed: true }
+          : { passed: false, message: `${rule.path} doesn't match ${rule.schema}:\n${errors.slice(0, MAX_SCHEMA_ERRORS).join("\n")}${more}` };
+      }
+
+      const selected = select(data, rule.select);
+      if (rule.exists === false) {
+        return selected.length === 0
+          ? { passed: true }
+          : { passed: false, message: `${rule.path}: ${selected[0].path} = ${showValue(selected[0].value)}, expected nothing at ${rule.select}` };
+      }
+      if (selected.length === 0) {
+        return { passed: false, message: `${rule.path}: nothing at ${rule.select}` };
+      }
+      // Every selected value has to pass
+      for (const { path, value } of selected) {
+        const problem = dataValueProblem(rule, value);
+        if (problem) {
+          return { passed: false, message: `${rule.path}: ${path} = ${showValue(value)}, ${problem}` };
+        }
+      }
+      return { passed: true };
+    }
+
+    case "files_unchanged":
+    case "only_changed":
+    case "no_new_files":
+    case "max_diff_lines": {
+      if (!ctx.changes) {
+        return { passed: false, error: true, message: "No workspace snapshot to compare against" };
+      }
+
+      if (rule.type === "files_unchanged") {
+        const changed = ctx.changes.filter((c) => pathMatches(c.path, rule.paths));
+        return changed.length === 0
+          ? { passed: true }
+          : { passed: false, message: changesMessage(`Changed: ${describeChanges(changed)}`, changed) };
+      }
+
+      if (rule.type === "only_changed") {
+        const outside = ctx.changes.filter((c) => !pathMatches(c.path, rule.paths));
+        return outside.length === 0
+          ? { passed: true }
+          : { passed: false, message: changesMessage(`Changed outside ${rule.paths.join(", ")}: ${describeChanges(outside)}`, outside) };
       }
 
-      const content = readFileSync(logPath, "utf-8");
-      const lines = content.trim().split("\n").filter(Boolean);
+      if (rule.type === "no_new_files") {
+        const created = ctx.changes.filter((c) => c.kind === "added" && !pathMatches(c.path, rule.except ?? []));
+        return created.length === 0
+          ? { passed: true }
+          : { passed: false, message: changesMessage(`New files: ${created.map((c) => c.path).join(", ")}`, created) };
+      }
+
+      const counted = rule.paths ? ctx.changes.filter((c) => pathMatches(c.path, rule.paths!)) : ctx.changes;
+      const total = counted.reduce((sum, c) => sum + c.added + c.removed, 0);
+      return total <= rule.max
+        ? { passed: true }
+        : { passed: false, message: changesMessage(`${total} lines changed (max ${rule.max})`, counted) };
+    }
+
+    case "tool_called": {
+      const toolCalls = readToolCalls(workdir);
 
-      // Find all calls to the specified tool
-      const matchingCalls = lines
-        .map((line) => {
-          try {
-            return JSON.parse(line);
-          } catch {
-            return null;
-          }
-        })
-        .filter((entry) => entry?.tool === rule.tool);
+      // Find all calls to the specified tool that actually ran
+      const matchingCalls = callsThatRan(toolCalls).filter((entry) => entry.tool === rule.tool);
 
       if (matchingCalls.length === 0) {
         return { passed: false, message: `Tool not called: ${rule.tool}` };
       }
 
-      // If no arg requirements, just check tool was called
-      if (!rule.args) {
+      // Check if any call matches the arg requirements (if there are any)
+      if (matchingCalls.some((call) => callMatches(call, rule))) {
         return { passed: true };
       }
 
-      // Check if any call matches the arg requirements
-      for (const call of matchingCalls) {
-        const args = call.arguments || {};
-        let allMatch = true;
-
-        for (const [key, expected] of Object.entries(rule.args)) {
-          const actual = args[key];
-          if (actual === undefined) {
-            allMatch = false;
-            break;
-          }
-
-          // If expected starts/ends with /, treat as regex pattern
-          if (typeof expected === "string" && expected.startsWith("/") && expected.endsWith("/")) {
-            const pattern = new RegExp(expected.slice(1, -1), "i");
-            if (!pattern.test(String(actual))) {
-              allMatch = false;
-              break;
-            }
-          } else {
-            // Exact match (case-insensitive for strings)
-            const actualStr = String(actual).toLowerCase();
-            const expectedStr = String(expected).toLowerCase();
-            if (!actualStr.includes(expectedStr)) {
-              allMatch = false;
-              break;
-            }
-          }
+      return {
+        passed: false,
+        message: `Tool ${rule.tool} called but args didn't match: expected ${JSON.stringify(rule.args)}`,
+      };
+    }
+
+    case "tool_not_called": {
+      const toolCalls = readToolCalls(workdir);
+
+      // Attempts count even when an injected fault stopped them: the agent
+      // still tried to do the forbidden thing
+      const patterns = Array.isArray(rule.tool) ? rule.tool : [rule.tool];
+      const offending = toolCalls.filter((call) =>
+        patterns.some((pattern) => globMatches(pattern, call.tool)) &&
+        (!rule.args || argsMatch(call.arguments || {}, rule.args))
+      );
+      if (offending.length === 0) {
+        return { passed: true };
+      }
+      const more = offending.length > 1 ? ` (and ${offending.length - 1} more)` : "";
+      return { passed: false, message: `Forbidden call: ${formatToolCall(offending[0])}${more}` };
+    }
+
+    case "tool_sequence": {
+      const toolCalls = readToolCalls(workdir);
+
+      // Each step must match a call after the previous step's call; other calls
+      // may come in between. Taking the earliest match each time is enough.
+      const calls = callsThatRan(toolCalls);
+      const steps = rule.tools.map((step): ToolMatch => (typeof step === "string" ? { tool: step } : step));
+      let position = -1;
+      for (const [i, step] of steps.entries()) {
+        const found = calls.findIndex((call, index) => index > position && callMatches(call, step));
+        if (found === -1) {
+          const label = step.args ? `${step.tool} ${JSON.stringify(step.args)}` : step.tool;
+          const after = i > 0 ? ` after ${formatToolCall(calls[position])}` : "";
+          return {
+            passed: false,
+            message: `Sequence broken at step ${i + 1}: no ${label} call${after}. Calls: ${calls.map((c) => c.tool).join(" → ") || "none"}`,
+          };
         }
+        position = found;
+      }
+      return { passed: true };
+    }
+
+    case "tool_call_count": {
+      const toolCalls = readToolCalls(workdir);
+
+      const n = callsThatRan(toolCalls).filter((call) => callMatches(call, rule)).length;
+      const what = rule.args ? `${rule.tool} ${JSON.stringify(rule.args)}` : rule.tool;
+      const problem =
+        rule.count !== undefined && n !== rule.count ? `expected exactly ${rule.count}`
+        : rule.min !== undefined && n < rule.min ? `expected at least ${rule.min}`
+        : rule.max !== undefined && n > rule.max ? `expected at most ${rule.max}`
+        : undefined;
+      return {
+        passed: !problem,
+        message: problem ? `${what} called ${n} time${n === 1 ? "" : "s"}, ${problem}` : undefined,
+      };
+    }
+
+    case "tool_arg_from_result": {
+      const toolCalls = readToolCalls(workdir);
 
-        if (allMatch) {
+      // Some call to `tool` must pass, as `arg`, a value returned by an
+      // earlier call to `from` (at `path` in its result, if given)
+      const calls = callsThatRan(toolCalls);
+      const targets = calls.filter((call) => call.tool === rule.tool && call.arguments?.[rule.arg] !== undefined);
+      if (targets.length === 0) {
+        return { passed: false, message: `No ${rule.tool} call with a ${rule.arg} argument` };
+      }
+      for (const target of targets) {
+        const earlier = calls.slice(0, calls.indexOf(target)).filter((call) => call.tool === rule.from);
+        const values = earlier.flatMap((call) => valuesAtPath(call.result, rule.path));
+        const actual = String(target.arguments[rule.arg]);
+        if (values.some((v) => v !== null && typeof v !== "object" && String(v) === actual)) {
           return { passed: true };
         }
       }
+      const used = [...new Set(targets.map((call) => JSON.stringify(call.arguments[rule.arg])))].join(", ");
+      return {
+        passed: false,
+        message: `${rule.tool}.${rule.arg} (${used}) never matched a value from an earlier ${rule.from} result${rule.path ? ` at ${rule.path}` : ""}`,
+      };
+    }
+
+    case "judge": {
+      if (!ctx.judge) {
+        return { passed: false, error: true, message: "No grader configured (add judge: to config.yaml)" };
+      }
+      const input = rule.input ?? (rule.path ? "file" : "transcript");
+      let label: string;
+      let material: string;
+      if (input === "file") {
+        const fullPath = rule.path ? join(workdir, rule.path) : undefined;
+        if (!fullPath || !existsSync(fullPath)) {
+          return { passed: false, error: true, message: `File not found: ${rule.path ?? "(no path)"}` };
+        }
+        label = `File ${rule.path}`;
+        material = readFileSync(fullPath, "utf-8");
+      } else if (input === "tool_calls") {
+        const toolCalls = readToolCalls(workdir);
+        label = "Tool calls, in order, with their results";
+        material = toolCalls.map((c) => `${formatToolCall(c)} -> ${JSON.stringify(c.result)}`).join("\n") || "(no tool calls)";
+      } else {
+        label = "Transcript";
+        material = ctx.transcript;
+      }
 
+      try {
+        const verdict = await judge(ctx.judge, rule.rubric, label, material);
+        const threshold = rule.threshold ?? DEFAULT_JUDGE_THRESHOLD;
+        const percent = (x: number) => `${Math.round(x * 100)}%`;
+        return {
+          passed: verdict.score >= threshold,
+          score: verdict.score,
+          message: `${percent(verdict.score)} (pass at ${percent(threshold)}${verdict.cached ? ", cached" : ""}): ${verdict.rationale}`,
+        };
+      } catch (err) {
+        return { passed: false, error: true, message: err instanceof Error ? err.message : String(err) };
+      }
+    }
+
+    case "all_of": {
+      const results = await validateAll(rule.rules, ctx);
+      const failed = results.filter((r) => !r.result.passed);
+      // One real failure is a verdict, whatever else couldn't be evaluated
+      return failed.length === 0
+        ? { passed: true }
+        : {
+          passed: false,
+          error: failed.every((r) => r.result.error),
+          message: `${failed.length} of ${results.length} failed:\n${nestedResults(failed)}`,
+        };
+    }
+
+    case "any_of": {
+      // Stop at the first alternative that passes
+      const results: Array<{ rule: ValidationRule; result: ValidationResult }> = [];
+      for (const nested of rule.rules) {
+        const result = await validateRule(nested, ctx);
+        if (result.passed) return { passed: true };
+        results.push({ rule: nested, result });
+      }
+      // One alternative that really failed is a verdict, as in all_of
       return {
         passed: false,
-        message: `Tool ${rule.tool} called but args didn't match: expected ${JSON.stringify(rule.args)}`,
+        error: results.every((r) => r.result.error),
+        message: `none of ${results.length} passed:\n${nestedResults(results)}`,
       };
     }
 
+    case "not": {
+      // Only a real verdict is inverted: a missing file stays a failure
+      const result = await validateRule(rule.rule, ctx);
+      if (result.error) return { passed: false, error: true, message: result.message };
+      return result.passed
+        ? { passed: false, message: `expected to fail, but passed: ${validationLabel(rule.rule)}` }
+        : { passed: true };
+    }
+
     case "custom": {
-      // Custom validators loaded dynamically
-      return { passed: false, message: "Custom validators not yet implemented" };
+      try {
+        const validator = await loadCustomValidator(rule.fn, ctx.scenario.dir ?? process.cwd());
+        const result = await validator({
+          workdir,
+          toolCalls: readToolCalls(workdir),
+          transcript: ctx.transcript,
+          turnIndex: ctx.turnIndex,
+          scenario: ctx.scenario,
+        });
+        // Anything but a verdict is the validator's fault, not the model's
+        if (typeof result?.passed !== "boolean") {
+          return { passed: false, error: true, message: `Custom validator ${rule.fn} returned no verdict: ${JSON.stringify(result)}` };
+        }
+        // Partial credit must be a number; out-of-range ones are clamped to 0-1
+        const score = result.score;
+        if (score !== undefined && !Number.isFinite(score)) {
+          return { passed: false, error: true, message: `Custom validator ${rule.fn} returned an invalid score: ${String(score)}` };
+        }
+        return {
+          passed: result.passed,
+          message: result.message,
+          score: score === undefined ? undefined : clampScore(score),
+        };
+      } catch (err) {
+        return { passed: false, error: true, message: `Custom validator ${rule.fn} threw: ${String(err)}` };
+      }
     }
 
     default:
-      return { passed: false, message: `Unknown rule type` };
+      return { passed: false, error: true, message: `Unknown rule type` };
   }
 }
 
-export function validateAll(
+export async function validateAll(
   rules: ValidationRule[],
-  workdir: string
-): Array<{ rule: ValidationRule; result: ValidationResult }> {
-  return rules.map((rule) => ({
+  ctx: ValidationContext
+): Promise<Array<{ rule: ValidationRule; result: ValidationResult }>> {
+  const results: Array<{ rule: ValidationRule; result: ValidationResult }> = [];
+  for (const rule of rules) {
+    results.push({ rule, result: await validateRule(rule, ctx) });
+  }
+  return results;
+}
+
+/** One "- label: message" line per nested result, nested messages indented */
+function nestedResults(results: Array<{ rule: ValidationRule; result: ValidationResult }>): string {
+  return results
+    .map(({ rule, result }) => `- ${validationLabel(rule)}${result.message ? `: ${result.message}` : ""}`.replace(/\n/g, "\n  "))
+    .join("\n");
+}
+
+/** Rules plus everything nested in all_of / any_of / not, depth first */
+export function flattenRules(rules: ValidationRule[]): ValidationRule[] {
+  return rules.flatMap((rule) => [
     rule,
-    result: validateRule(rule, workdir),
-  }));
+    ...flattenRules(rule.type === "all_of" || rule.type === "any_of" ? rule.rules : rule.type === "not" ? [rule.rule] : []),
+  ]);
+}
+
+/** Every rule of a scenario, across its turns */
+export function scenarioRules(scenario: Scenario): ValidationRule[] {
+  return [...(scenario.validate ?? []), ...(scenario.turns ?? []).flatMap((t) => t.validate)];
+}
+
+/**
+ * Whether failing this rule fails the run: every rule does, unless the
+ * scenario marks some rules critical, then only those
+ */
+export function failsRun(rule: ValidationRule, rules: ValidationRule[]): boolean {
+  return rule.critical ?? !rules.some((r) => r.critical);
+}
+
+function clampScore(score: number): number {
+  return Math.min(1, Math.max(0, score));
+}
+
+/**
+ * 0-100: the weighted share of the scenario's checks that passed. A check's
+ * own 0-1 score (judge, custom) counts as partial credit, clamped to 0-1 (a
+ * non-finite one is ignored); checks that never ran (the run stopped at an
+ * earlier turn) count as failed.
+ */
+export function runScore(
+  scenario: Scenario,
+  validations: Array<{ rule: ValidationRule; passed: boolean; score?: number }>
+): number {
+  const weightOf = (rule: ValidationRule) => rule.weight ?? 1;
+  const total = scenarioRules(scenario).reduce((sum, rule) => sum + weightOf(rule), 0);
+  if (total <= 0) return 100;  // nothing to check
+  const creditOf = (v: { passed: boolean; score?: number }) =>
+    v.score !== undefined && Number.isFinite(v.score) ? clampScore(v.score) : v.passed ? 1 : 0;
+  const earned = validations.reduce((sum, v) => sum + weightOf(v.rule) * creditOf(v), 0);
+  return Math.round((earned / total) * 100);
+}
+
+/** Short human-readable name for a rule, used in reports */
+export function validationLabel(rule: ValidationRule): string {
+  const r = rule as any;
+  if (r.name) return r.name;
+  if (r.type === "tool_called") return `tool_called: ${r.tool}`;
+  if (r.type === "tool_not_called") return `tool_not_called: ${[r.tool].flat().join(", ")}`;
+  if (r.type === "tool_sequence") {
+    return `tool_sequence: ${r.tools.map((t: string | ToolMatch) => (typeof t === "string" ? t : t.tool)).join(" → ")}`;
+  }
+  if (r.type === "tool_call_count") return `tool_call_count: ${r.tool}`;
+  if (r.type === "tool_arg_from_result") return `tool_arg_from_result: ${r.from} → ${r.tool}.${r.arg}`;
+  if (r.type === "custom") return `custom: ${r.fn}`;
+  if (r.type === "all_of") return `all_of: ${r.rules.map(validationLabel).join(" & ")}`;
+  if (r.type === "any_of") return `any_of: ${r.rules.map(validationLabel).join(" | ")}`;
+  if (r.type === "not") return `not: ${validationLabel(r.rule)}`;
+  if (r.type === "judge") return `judge: ${r.path ?? r.input ?? "transcript"}`;
+  if (r.type === "command_output") return `command_output: ${r.command}`;
+  if (r.type === "data_path") return `data_path: ${r.path} ${r.select}`;
+  if (r.type === "data_schema") return `data_schema: ${r.path}`;
+  if (r.type === "files_unchanged" || r.type === "only_changed") return `${r.type}: ${r.paths.join(", ")}`;
+  if (r.type === "max_diff_lines") return `max_diff_lines: ${r.max}`;
+  return r.type + ("path" in r ? `: ${r.path}` : "");
 }
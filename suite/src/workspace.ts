@@ -0,0 +1,235 @@
+import { readdirSync, readFileSync, statSync } from "node:fs";
+import { join, sep } from "node:path";
+import { createHash } from "node:crypto";
+
+// Workspace snapshots: the runner snapshots the workdir right after setup and
+// diffs it after each agent turn, for the files_unchanged / only_changed /
+// no_new_files / max_diff_lines rules and the per-run diff in the report.
+
+/** Files the runners, the MCP harness and common build tools write; never part of a diff */
+const IGNORED = [
+  "tool-calls.log",
+  "harness-state.json",
+  ".harness-*.json",
+  ".goose-prompt.txt",
+  ".opencode-prompt.txt",
+  ".pi-prompt.txt",
+  ".command-prompt.txt",
+  ".pi-session*.jsonl",
+  "opencode.json",
+  ".pi/**",
+  ".opencode/**",
+  ".goose/**",
+  ".git/**",
+  "node_modules/**",
+  "target/**",
+].map(globToRegExp);
+
+const MAX_TEXT_BYTES = 1024 * 1024;   // larger files are compared by hash only
+const MAX_DIFF_CELLS = 4_000_000;     // LCS table limit; beyond it the whole file is -/+
+const CONTEXT_LINES = 3;
+
+export const WORKSPACE_DIFF_HEADER = `\n${"=".repeat(60)}\nWORKSPACE DIFF\n${"=".repeat(60)}\n`;
+
+interface FileState {
+  hash: string;
+  text?: string;  // absent for binary and very large files
+}
+
+/** Relative path (always with /) -> contents */
+export type WorkspaceSnapshot = Map<string, FileState>;
+
+export interface FileChange {
+  path: string;
+  kind: "added" | "modified" | "deleted";
+  added: number;    // lines
+  removed: number;
+  diff: string;     // unified diff
+}
+
+/**
+ * Glob for workspace paths: `*` and `?` stay within a path segment, `**`
+ * spans directories. A trailing / means everything below.
+ */
+export function globToRegExp(glob: string): RegExp {
+  const pattern = glob.endsWith("/") ? glob + "**" : glob;
+  let re = "";
+  for (let i = 0; i < pattern.length; i++) {
+    const c = pattern[i];
+    if (c === "*" && pattern[i + 1] === "*") {
+      i++;
+      if (pattern[i + 1] === "/") {
+        i++;
+        re += "(?:.*/)?";
+      } else {
+        re += ".*";
+      }
+    } else if (c === "*") {
+      re += "[^/]*";
+    } else if (c === "?") {
+      re += "[^/]";
+    } else {
+      re += c.replace(/[.+^${}()|[\]\\]/g, "\\$&");
+    }
+  }
+  return new RegExp(`^${re}$`);
+}
+
+/** Whether a path matches any glob, either itself or through a parent directory ("src" covers src/a.rs) */
+export function pathMatches(path: string, globs: string[]): boolean {
+  const parts = path.split("/");
+  const candidates = parts.map((_, i) => parts.slice(0, i + 1).join("/"));
+  return globs.some((glob) => {
+    const regex = globToRegExp(glob);
+    return candidates.some((candidate) => regex.test(candidate));
+  });
+}
+
+function isIgnored(path: string): boolean {
+  return IGNORED.some((regex) => regex.test(path));
+}
+
+export function takeSnapshot(dir: string): WorkspaceSnapshot {
+  const snapshot: WorkspaceSnapshot = new Map();
+  for (const entry of readdirSync(dir, { recursive: true }) as string[]) {
+    const path = entry.split(sep).join("/");
+    if (isIgnored(path)) continue;
+    const fullPath = join(dir, entry);
+    try {
+      if (!statSync(fullPath).isFile()) continue;
+      const data = readFileSync(fullPath);
+      const isText = data.length <= MAX_TEXT_BYTES && !data.includes(0);
+      snapshot.set(path, {
+        hash: createHash("sha256").update(data).digest("hex"),
+        text: isText ? data.toString("utf-8") : undefined,
+      });
+    } catch (e) { /* vanished or unreadable */ }
+  }
+  return snapshot;
+}
+
+function splitLines(text: string): string[] {
+  const lines = text.split("\n");
+  if (lines[lines.length - 1] === "") lines.pop();
+  return lines;
+}
+
+type DiffOp = { kind: " " | "-" | "+"; line: string };
+
+/** Line diff via longest common subsequence, after trimming the common prefix/suffix */
+function diffLines(a: string[], b: string[]): DiffOp[] {
+  let start = 0;
+  while (start < a.length && start < b.length && a[start] === b[start]) start++;
+  let endA = a.length;
+  let endB = b.length;
+  while (endA > start && endB > start && a[endA - 1] === b[endB - 1]) {
+    endA--;
+    endB--;
+  }
+  const x = a.slice(start, endA);
+  const y = b.slice(start, endB);
+
+  const ops: DiffOp[] = a.slice(0, start).map((line) => ({ kind: " ", line }));
+  if (x.length * y.length > MAX_DIFF_CELLS) {
+    ops.push(...x.map((line): DiffOp => ({ kind: "-", line })), ...y.map((line): DiffOp => ({ kind: "+", line })));
+  } else {
+    // lcs[i][j] = length of the LCS of x[i:] and y[j:]
+    const lcs = Array.from({ length: x.length + 1 }, () => new Uint32Array(y.length + 1));
+    for (let i = x.length - 1; i >= 0; i--) {
+      for (let j = y.length - 1; j >= 0; j--) {
+        lcs[i][j] = x[i] === y[j] ? lcs[i + 1][j + 1] + 1 : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+      }
+    }
+    let i = 0;
+    let j = 0;
+    while (i < x.length && j < y.length) {
+      if (x[i] === y[j]) {
+        ops.push({ kind: " ", line: x[i] });
+        i++;
+        j++;
+      } else if (lcs[i + 1][j] >= lcs[i][j + 1]) {
+        ops.push({ kind: "-", line: x[i++] });
+      } else {
+        ops.push({ kind: "+", line: y[j++] });
+      }
+    }
+    while (i < x.length) ops.push({ kind: "-", line: x[i++] });
+    while (j < y.length) ops.push({ kind: "+", line: y[j++] });
+  }
+  ops.push(...a.slice(endA).map((line): DiffOp => ({ kind: " ", line })));
+  return ops;
+}
+
+/** Unified diff of one file (undefined = file absent on that side) */
+export function unifiedDiff(
+  path: string,
+  before: string | undefined,
+  after: string | undefined
+): { diff: string; added: number; removed: number } {
+  const ops = diffLines(before === undefined ? [] : splitLines(before), after === undefined ? [] : splitLines(after));
+
+  // 1-based line numbers in a and b where each op sits
+  const positions: Array<[number, number]> = [];
+  let lineA = 1;
+  let lineB = 1;
+  for (const op of ops) {
+    positions.push([lineA, lineB]);
+    if (op.kind !== "+") lineA++;
+    if (op.kind !== "-") lineB++;
+  }
+
+  const hunks: string[] = [];
+  let i = 0;
+  while (i < ops.length) {
+    if (ops[i].kind === " ") {
+      i++;
+      continue;
+    }
+    // Extend the hunk while the next change is within 2x context lines
+    let lastChange = i;
+    for (let k = i; k < ops.length && k - lastChange <= 2 * CONTEXT_LINES; k++) {
+      if (ops[k].kind !== " ") lastChange = k;
+    }
+    const start = Math.max(0, i - CONTEXT_LINES);
+    const end = Math.min(ops.length, lastChange + CONTEXT_LINES + 1);
+    const slice = ops.slice(start, end);
+    const countA = slice.filter((op) => op.kind !== "+").length;
+    const countB = slice.filter((op) => op.kind !== "-").length;
+    const [startA, startB] = positions[start];
+    hunks.push(
+      `@@ -${countA ? startA : startA - 1},${countA} +${countB ? startB : startB - 1},${countB} @@\n` +
+      slice.map((op) => `${op.kind}${op.line}\n`).join("")
+    );
+    i = end;
+  }
+
+  const header = `--- ${before === undefined ? "/dev/null" : `a/${path}`}\n+++ ${after === undefined ? "/dev/null" : `b/${path}`}\n`;
+  return {
+    diff: header + hunks.join(""),
+    added: ops.filter((op) => op.kind === "+").length,
+    removed: ops.filter((op) => op.kind === "-").length,
+  };
+}
+
+/** Everything that changed in dir since the snapshot, sorted by path */
+export function diffWorkspace(before: WorkspaceSnapshot, dir: string): FileChange[] {
+  const after = takeSnapshot(dir);
+  const paths = [...new Set([...before.keys(), ...after.keys()])].sort();
+  const changes: FileChange[] = [];
+  for (const path of paths) {
+    const a = before.get(path);
+    const b = after.get(path);
+    if (a && b && a.hash === b.hash) continue;
+    const kind = !a ? "added" : !b ? "deleted" : "modified";
+    if ((a && a.text === undefined) || (b && b.text === undefined)) {
+      changes.push({ path, kind, added: 0, removed: 0, diff: `Binary file ${path} ${kind}\n` });
+      continue;
+    }
+    changes.push({ path, kind, ...unifiedDiff(path, a?.text, b?.text) });
+  }
+  return changes;
+}
+
+export function formatChanges(changes: FileChange[]): string {
+  return changes.length ? changes.map((c) => c.diff).join("") : "(no changes)\n";
+}